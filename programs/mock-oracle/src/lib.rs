@@ -0,0 +1,89 @@
+//! Writes oracle-shaped accounts so NightFury's feed-driven modes can be tested on a local
+//! validator. Accounts are created by the client with this program as owner and sized for
+//! the layout they hold.
+
+use anchor_lang::prelude::*;
+
+declare_id!("3RU678TWk6vYWodBsMtNWtptC4Ep5qoH39uECdAWQzGZ");
+
+/// Bytes needed for the Pyth price fields written by `set_price`.
+pub const PRICE_ACCOUNT_LENGTH: usize = 240;
+/// Bytes of NightFury's generic data feed layout written by `set_value`.
+pub const DATA_FEED_LENGTH: usize = 20;
+
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    /// Writes a Pyth v2 price account: header, exponent, timestamp and aggregate price.
+    pub fn set_price(
+        ctx: Context<SetPrice>,
+        price: i64,
+        conf: u64,
+        expo: i32,
+        status: u32,
+    ) -> Result<()> {
+        let mut data = ctx.accounts.price.try_borrow_mut_data()?;
+        require!(
+            data.len() >= PRICE_ACCOUNT_LENGTH,
+            MockOracleError::AccountTooSmall
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        write(&mut data, 0, &0xa1b2_c3d4u32.to_le_bytes());
+        write(&mut data, 4, &2u32.to_le_bytes());
+        write(&mut data, 8, &3u32.to_le_bytes());
+        write(&mut data, 12, &(PRICE_ACCOUNT_LENGTH as u32).to_le_bytes());
+        write(&mut data, 20, &expo.to_le_bytes());
+        write(&mut data, 96, &timestamp.to_le_bytes());
+        write(&mut data, 208, &price.to_le_bytes());
+        write(&mut data, 216, &conf.to_le_bytes());
+        write(&mut data, 224, &status.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Writes a generic data feed value, stamped with `timestamp` or the current time so
+    /// tests can publish stale readings.
+    pub fn set_value(ctx: Context<SetValue>, value: u64, timestamp: Option<i64>) -> Result<()> {
+        let mut data = ctx.accounts.feed.try_borrow_mut_data()?;
+        require!(
+            data.len() >= DATA_FEED_LENGTH,
+            MockOracleError::AccountTooSmall
+        );
+
+        let timestamp = match timestamp {
+            Some(timestamp) => timestamp,
+            None => Clock::get()?.unix_timestamp,
+        };
+        write(&mut data, 0, &0x4e46_4446u32.to_le_bytes());
+        write(&mut data, 4, &value.to_le_bytes());
+        write(&mut data, 12, &timestamp.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    /// CHECK: raw oracle bytes, only needs to be owned by this program.
+    #[account(mut, owner = crate::ID)]
+    pub price: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetValue<'info> {
+    /// CHECK: raw oracle bytes, only needs to be owned by this program.
+    #[account(mut, owner = crate::ID)]
+    pub feed: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum MockOracleError {
+    #[msg("Account too small for this layout")]
+    AccountTooSmall,
+}
+
+fn write(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
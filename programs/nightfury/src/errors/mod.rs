@@ -8,6 +8,10 @@ pub enum NightFuryError {
     InvalidMint,
     #[msg("Uri too long")]
     UriTooLong,
+    #[msg("Name too long")]
+    NameTooLong,
+    #[msg("Symbol too long")]
+    SymbolTooLong,
     FailedToBuildUpdateInstruction,
     #[msg("Invalid Instructions Sysvar Id")]
     InvalidInstructionsSysvarId,
@@ -21,4 +25,118 @@ pub enum NightFuryError {
     InvalidAuthRulesProgram,
     #[msg("Invalid Delegate Instruction")]
     InvalidDelegateInstruction,
+    #[msg("Invalid number of phases")]
+    InvalidPhaseCount,
+    #[msg("Invalid phase index")]
+    InvalidPhaseIndex,
+    #[msg("Phase period must be positive")]
+    InvalidPeriod,
+    #[msg("Invalid latitude or longitude")]
+    InvalidCoordinates,
+    #[msg("UTC offset out of range")]
+    InvalidUtcOffset,
+    #[msg("Override must end after it starts")]
+    InvalidOverrideWindow,
+    #[msg("Too many overrides")]
+    TooManyOverrides,
+    #[msg("Invalid override index")]
+    InvalidOverrideIndex,
+    #[msg("NightFury is not active")]
+    NotActive,
+    #[msg("NightFury is not paused")]
+    NotPaused,
+    #[msg("Expiry needs a future end time or a positive switch count")]
+    InvalidExpiry,
+    #[msg("Thresholds must be ascending, one fewer than the phases")]
+    InvalidThresholds,
+    #[msg("Oracle account does not match the configured feed")]
+    InvalidOracle,
+    #[msg("Invalid price feed account")]
+    InvalidPriceFeed,
+    #[msg("Price feed is not trading")]
+    PriceUnavailable,
+    #[msg("Feed rules must start at zero, ascend and point at existing phases")]
+    InvalidFeedRules,
+    #[msg("Feed max age must be positive")]
+    InvalidMaxAge,
+    #[msg("Invalid data feed account")]
+    InvalidDataFeed,
+    #[msg("Data feed is stale")]
+    StaleDataFeed,
+    #[msg("Cooldown must not be negative")]
+    InvalidCooldown,
+    #[msg("Holder switching is disabled")]
+    HolderSwitchDisabled,
+    #[msg("Holder switch is cooling down")]
+    CooldownActive,
+    #[msg("Token account is not held by the signer")]
+    InvalidHolder,
+    #[msg("NightFury has finished")]
+    AlreadyFinished,
+    #[msg("Pin must end in the future")]
+    InvalidPinWindow,
+    #[msg("Phase is pinned by the authority")]
+    PhasePinned,
+    #[msg("Signer is not the config's thread")]
+    InvalidThread,
+    #[msg("Switch is not due yet")]
+    CrankNotDue,
+    #[msg("Not supported by this config's scheduler")]
+    UnsupportedScheduler,
+    #[msg("Escrow funding must cover rent")]
+    InvalidFunding,
+    #[msg("Invalid cron schedule")]
+    InvalidSchedule,
+    #[msg("Cron schedule is too long")]
+    ScheduleTooLong,
+    #[msg("Cron schedule never fires again")]
+    ScheduleExhausted,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Not enough lamports to withdraw")]
+    InsufficientFunds,
+    #[msg("Thread fee is out of range")]
+    InvalidThreadFee,
+    #[msg("Rate limit must be greater than zero")]
+    InvalidRateLimit,
+    #[msg("Overrides belong to another config")]
+    InvalidOverrides,
+    #[msg("Invalid batch page size")]
+    InvalidPageSize,
+    #[msg("Batch is full")]
+    BatchFull,
+    #[msg("Config is already in the batch")]
+    AlreadyInBatch,
+    #[msg("Config is not in the batch")]
+    NotInBatch,
+    #[msg("Batch still has members")]
+    BatchNotEmpty,
+    #[msg("Accounts don't match the batch page")]
+    InvalidBatchPage,
+    #[msg("Mint is not a verified member of the collection")]
+    InvalidCollection,
+    #[msg("Authorization rules don't match the collection's")]
+    InvalidAuthRules,
+    #[msg("Collection still has members")]
+    CollectionNotEmpty,
+    #[msg("Accounts don't match the collection page")]
+    InvalidCollectionPage,
+    #[msg("Accounts don't match the members being registered")]
+    InvalidMemberAccounts,
+    #[msg("One-way modes can't go back to an earlier phase")]
+    OneWayMode,
+    #[msg("Can't reveal before the reveal time")]
+    RevealNotDue,
+    #[msg("Names and symbols must be set by every phase or by none")]
+    InconsistentPhases,
+    #[msg("Overrides can only set a name or symbol the phases also set")]
+    UnrestorableOverride,
+    #[msg("Observer altitude out of range")]
+    InvalidAltitude,
+    #[msg("Not a config in the legacy day/night layout")]
+    InvalidLegacyConfig,
+    #[msg("Price feed is stale")]
+    StalePriceFeed,
+    #[msg("Thread id too long")]
+    ThreadIdTooLong,
 }
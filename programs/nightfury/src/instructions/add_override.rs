@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::state::{NightFury, Override, Overrides, Phase};
+
+#[derive(Accounts)]
+pub struct AddOverride<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    #[account(
+        init_if_needed,
+        space = Overrides::LENGTH,
+        payer = authority,
+        seeds = [b"overrides".as_ref(), nightfury.key().as_ref()],
+        bump
+    )]
+    pub overrides: Box<Account<'info, Overrides>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_add_override(
+    ctx: Context<AddOverride>,
+    start_at: i64,
+    end_at: i64,
+    phase: Phase,
+) -> Result<()> {
+    require!(start_at < end_at, NightFuryError::InvalidOverrideWindow);
+    // Overrides can show any art, earlier stages included.
+    require!(
+        !ctx.accounts.nightfury.mode.is_one_way(),
+        NightFuryError::OneWayMode
+    );
+    phase.validate()?;
+    // Once the override ends the schedule only puts back what its phases set, which is the
+    // same for all of them.
+    let base_phase = &ctx.accounts.nightfury.phases[0];
+    require!(
+        (phase.name.is_none() || base_phase.name.is_some())
+            && (phase.symbol.is_none() || base_phase.symbol.is_some()),
+        NightFuryError::UnrestorableOverride
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let overrides = &mut ctx.accounts.overrides;
+    overrides.nightfury = ctx.accounts.nightfury.key();
+    overrides.bump = *ctx.bumps.get("overrides").unwrap();
+
+    // Make room by dropping overrides that have already ended.
+    overrides.entries.retain(|entry| entry.end_at > now);
+    require!(
+        overrides.entries.len() < Overrides::MAX_ENTRIES,
+        NightFuryError::TooManyOverrides
+    );
+    overrides.entries.push(Override {
+        start_at,
+        end_at,
+        phase,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::{
+    pda::find_metadata_account,
+    state::{Metadata, TokenMetadataAccount},
+    utils::assert_owned_by,
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::rule_set;
+use crate::state::{Batch, BatchMember, NightFury, Scheduler};
+
+#[derive(Accounts)]
+pub struct AddToBatch<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub batch: Box<Account<'info, Batch>>,
+    #[account(has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    /// CHECK: the config's metadata, read for its rule set.
+    #[account(address = find_metadata_account(&nightfury.mint).0)]
+    pub metadata: UncheckedAccount<'info>,
+}
+
+pub fn process_add_to_batch(ctx: Context<AddToBatch>) -> Result<()> {
+    let nightfury = &ctx.accounts.nightfury;
+    let batch = &mut ctx.accounts.batch;
+    require!(
+        nightfury.scheduler == Scheduler::Batch { batch: batch.key() },
+        NightFuryError::NotInBatch
+    );
+    // Pages carry a fixed set of accounts per member, with no room for an oracle.
+    require!(
+        nightfury.mode.oracle().is_none(),
+        NightFuryError::UnsupportedScheduler
+    );
+    // Every page is updated with the batch's rule set.
+    assert_owned_by(&ctx.accounts.metadata, &mpl_token_metadata::id())?;
+    let metadata = Metadata::from_account_info(&ctx.accounts.metadata)?;
+    require!(
+        !matches!(rule_set(&metadata), Some(rule_set) if rule_set != batch.auth_rules),
+        NightFuryError::InvalidAuthRules
+    );
+    require!(
+        batch
+            .members
+            .iter()
+            .all(|member| member.nightfury != nightfury.key()),
+        NightFuryError::AlreadyInBatch
+    );
+
+    let member = BatchMember {
+        nightfury: nightfury.key(),
+        mint: nightfury.mint,
+    };
+    match batch.members.iter().position(BatchMember::is_vacant) {
+        Some(index) => batch.members[index] = member,
+        None => {
+            require!(
+                batch.members.len() < Batch::MAX_MEMBERS,
+                NightFuryError::BatchFull
+            );
+            batch.members.push(member);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::token::Token;
+
+use crate::errors::NightFuryError;
+use crate::instructions::{delegate_member, MemberAccounts};
+use crate::state::{Collection, CollectionMember};
+use crate::system::create_pda;
+
+/// Accounts `batch_register` takes per mint in `remaining_accounts`, in this order:
+/// member, mint, metadata, master edition, token account and delegate record.
+const ACCOUNTS_PER_MEMBER: usize = 6;
+
+#[derive(Accounts)]
+pub struct BatchRegister<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: matched against the collection's.
+    pub authorization_rules: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Registers every mint in `remaining_accounts` with the collection, the same way
+/// `register_member` does one. Members take consecutive indices, so their accounts have to
+/// be passed for `next_index` onwards.
+pub fn process_batch_register<'info>(
+    ctx: Context<'_, '_, '_, 'info, BatchRegister<'info>>,
+) -> Result<()> {
+    let groups = ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_MEMBER);
+    require!(
+        groups.len() > 0 && groups.remainder().is_empty(),
+        NightFuryError::InvalidMemberAccounts
+    );
+
+    let collection_key = ctx.accounts.collection.key();
+    let collection_info = ctx.accounts.collection.to_account_info();
+    for accounts in groups {
+        let member = &accounts[0];
+        let mint = &accounts[1];
+        let index = ctx.accounts.collection.next_index;
+        let (member_key, member_bump) = Pubkey::find_program_address(
+            &[
+                b"member".as_ref(),
+                collection_key.as_ref(),
+                index.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            member.key(),
+            member_key,
+            NightFuryError::InvalidMemberAccounts
+        );
+
+        delegate_member(
+            &ctx.accounts.collection,
+            &MemberAccounts {
+                authority: &ctx.accounts.authority.to_account_info(),
+                collection: &collection_info,
+                mint,
+                token_account: &accounts[4],
+                metadata: &accounts[2],
+                master_edition: &accounts[3],
+                delegate_record: &accounts[5],
+                auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+                authorization_rules_program: &ctx
+                    .accounts
+                    .authorization_rules_program
+                    .to_account_info(),
+                instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+        )?;
+
+        create_pda(
+            &ctx.accounts.authority.to_account_info(),
+            member,
+            &ctx.accounts.system_program.to_account_info(),
+            CollectionMember::LENGTH,
+            &[
+                b"member".as_ref(),
+                collection_key.as_ref(),
+                index.to_le_bytes().as_ref(),
+                &[member_bump],
+            ],
+        )?;
+        CollectionMember {
+            collection: collection_key,
+            mint: mint.key(),
+            index,
+            bump: member_bump,
+        }
+        .try_serialize(&mut &mut member.try_borrow_mut_data()?[..])?;
+
+        let collection = &mut ctx.accounts.collection;
+        collection.next_index += 1;
+        collection.member_count += 1;
+    }
+
+    Ok(())
+}
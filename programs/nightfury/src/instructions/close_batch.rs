@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_delete, ThreadDelete},
+    ThreadProgram,
+};
+
+use crate::errors::NightFuryError;
+use crate::state::Batch;
+
+#[derive(Accounts)]
+pub struct CloseBatch<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, close = authority, has_one = authority, has_one = thread)]
+    pub batch: Box<Account<'info, Batch>>,
+    /// CHECK: matched against the batch.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+}
+
+pub fn process_close_batch(ctx: Context<CloseBatch>) -> Result<()> {
+    let batch = &ctx.accounts.batch;
+    require!(batch.members.is_empty(), NightFuryError::BatchNotEmpty);
+
+    thread_delete(CpiContext::new_with_signer(
+        ctx.accounts.thread_program.to_account_info(),
+        ThreadDelete {
+            authority: batch.to_account_info(),
+            close_to: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+        },
+        &[&batch.signer_seeds()],
+    ))
+}
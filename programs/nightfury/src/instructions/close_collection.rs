@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_delete, ThreadDelete},
+    ThreadProgram,
+};
+
+use crate::errors::NightFuryError;
+use crate::state::Collection;
+
+#[derive(Accounts)]
+pub struct CloseCollection<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, close = authority, has_one = authority, has_one = thread)]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: matched against the collection.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+}
+
+pub fn process_close_collection(ctx: Context<CloseCollection>) -> Result<()> {
+    let collection = &ctx.accounts.collection;
+    require!(
+        collection.member_count == 0,
+        NightFuryError::CollectionNotEmpty
+    );
+
+    thread_delete(CpiContext::new_with_signer(
+        ctx.accounts.thread_program.to_account_info(),
+        ThreadDelete {
+            authority: collection.to_account_info(),
+            close_to: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+        },
+        &[&collection.signer_seeds()],
+    ))
+}
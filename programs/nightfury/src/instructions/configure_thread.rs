@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use clockwork_sdk::ThreadProgram;
+
+use crate::scheduler::SchedulerAccounts;
+use crate::state::{NightFury, ThreadConfig};
+
+#[derive(Accounts)]
+pub struct ConfigureThread<'info> {
+    pub authority: Signer<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, has_one = mint, has_one = thread, has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    /// CHECK: matched against the config, only exists for Clockwork configs.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_configure_thread(
+    ctx: Context<ConfigureThread>,
+    thread_config: ThreadConfig,
+) -> Result<()> {
+    thread_config.validate()?;
+    ctx.accounts.nightfury.thread_config = thread_config;
+
+    let nightfury = &ctx.accounts.nightfury;
+    nightfury.scheduler.backend().configure(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+    )?;
+
+    Ok(())
+}
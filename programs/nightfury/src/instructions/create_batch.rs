@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_create, ThreadCreate},
+    state::{Thread, Trigger},
+    ThreadProgram,
+};
+
+use crate::errors::NightFuryError;
+use crate::instructions::switch_batch_instruction;
+use crate::scheduler::cron;
+use crate::state::Batch;
+
+#[derive(Accounts)]
+#[instruction(thread_id: Vec<u8>)]
+pub struct CreateBatch<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        space = Batch::LENGTH,
+        payer = authority,
+        seeds = [b"batch".as_ref(), authority.key().as_ref(), thread_id.as_ref()],
+        bump
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+    /// CHECK: created by the thread program.
+    #[account(mut, address = Thread::pubkey(batch.key(), thread_id))]
+    pub thread: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the authorization_rules_program
+    pub authorization_rules: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_create_batch(
+    ctx: Context<CreateBatch>,
+    thread_id: Vec<u8>,
+    schedule: String,
+    page_size: u8,
+    funding: u64,
+) -> Result<()> {
+    cron::validate(&schedule, Clock::get()?.unix_timestamp)?;
+    require!(
+        (1..=Batch::MAX_PAGE_SIZE).contains(&page_size),
+        NightFuryError::InvalidPageSize
+    );
+
+    let batch = &mut ctx.accounts.batch;
+    batch.authority = ctx.accounts.authority.key();
+    batch.thread = ctx.accounts.thread.key();
+    batch.thread_id = thread_id;
+    batch.auth_rules = ctx.accounts.authorization_rules.key();
+    batch.schedule = schedule;
+    batch.page_size = page_size;
+    batch.members = Vec::new();
+    batch.bump = *ctx.bumps.get("batch").unwrap();
+
+    // The thread only ever kicks a run off, every page after that is a follow-up.
+    let batch = &ctx.accounts.batch;
+    thread_create(
+        CpiContext::new_with_signer(
+            ctx.accounts.thread_program.to_account_info(),
+            ThreadCreate {
+                payer: ctx.accounts.authority.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                thread: ctx.accounts.thread.to_account_info(),
+                authority: batch.to_account_info(),
+            },
+            &[&batch.signer_seeds()],
+        ),
+        funding,
+        batch.thread_id.clone(),
+        vec![switch_batch_instruction(batch.key(), batch, None).into()],
+        Trigger::Cron {
+            schedule: batch.schedule.clone(),
+            skippable: true,
+        },
+    )
+}
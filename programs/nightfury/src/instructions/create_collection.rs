@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use clockwork_sdk::{
+    cpi::{thread_create, ThreadCreate},
+    state::{Thread, Trigger},
+    ThreadProgram,
+};
+use mpl_token_metadata::{
+    state::{Metadata, TokenMetadataAccount},
+    utils::assert_owned_by,
+};
+
+use crate::errors::NightFuryError;
+use crate::instructions::switch_collection_instruction;
+use crate::scheduler::cron;
+use crate::state::{Collection, NightFury, Phase, PhaseMode, Timezone};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreateCollectionArgs {
+    pub phases: Vec<Phase>,
+    pub mode: PhaseMode,
+    pub timezone: Timezone,
+    /// Cron schedule `switch_collection` is cranked on, seconds through years, in UTC.
+    pub schedule: String,
+    /// Members switched per transaction.
+    pub page_size: u8,
+    /// Lamports the thread starts out with to pay for its executions.
+    pub funding: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(thread_id: Vec<u8>, args: CreateCollectionArgs)]
+pub struct CreateCollection<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub collection_mint: Box<Account<'info, Mint>>,
+    /// CHECK: make sure this is the collection mint's metadata and the authority updates it.
+    pub collection_metadata: UncheckedAccount<'info>,
+    #[account(
+        init,
+        space = Collection::space(args.phases.len()),
+        payer = authority,
+        seeds = [b"collection".as_ref(), collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: created by the thread program.
+    #[account(mut, address = Thread::pubkey(collection.key(), thread_id))]
+    pub thread: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the authorization_rules_program
+    pub authorization_rules: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_create_collection(
+    ctx: Context<CreateCollection>,
+    thread_id: Vec<u8>,
+    args: CreateCollectionArgs,
+) -> Result<()> {
+    assert_owned_by(
+        &ctx.accounts.authorization_rules,
+        &mpl_token_auth_rules::id(),
+    )?;
+    assert_owned_by(&ctx.accounts.collection_metadata, &mpl_token_metadata::id())?;
+    let collection_metadata = Metadata::from_account_info(&ctx.accounts.collection_metadata)?;
+    require!(
+        collection_metadata.mint == ctx.accounts.collection_mint.key(),
+        NightFuryError::InvalidMint
+    );
+    require!(
+        collection_metadata.update_authority == ctx.accounts.authority.key(),
+        NightFuryError::InvalidAuthority
+    );
+
+    NightFury::validate_phases(&args.phases)?;
+    args.mode.validate(args.phases.len())?;
+    // Pages carry a fixed set of accounts per member, with no room for an oracle.
+    require!(
+        args.mode.oracle().is_none(),
+        NightFuryError::UnsupportedScheduler
+    );
+    args.timezone.validate()?;
+    let now = Clock::get()?.unix_timestamp;
+    cron::validate(&args.schedule, now)?;
+    require!(
+        (1..=Collection::MAX_PAGE_SIZE).contains(&args.page_size),
+        NightFuryError::InvalidPageSize
+    );
+
+    let collection = &mut ctx.accounts.collection;
+    collection.authority = ctx.accounts.authority.key();
+    collection.collection_mint = ctx.accounts.collection_mint.key();
+    collection.phases = args.phases;
+    collection.phase = 0;
+    collection.phase_changed_at = now;
+    collection.mode = args.mode;
+    collection.timezone = args.timezone;
+    collection.schedule = args.schedule;
+    collection.auth_rules = ctx.accounts.authorization_rules.key();
+    collection.page_size = args.page_size;
+    collection.next_index = 0;
+    collection.member_count = 0;
+    collection.thread = ctx.accounts.thread.key();
+    collection.thread_id = thread_id;
+    collection.bump = *ctx.bumps.get("collection").unwrap();
+
+    // The thread only ever kicks a run off, every page after that is a follow-up.
+    let collection = &ctx.accounts.collection;
+    thread_create(
+        CpiContext::new_with_signer(
+            ctx.accounts.thread_program.to_account_info(),
+            ThreadCreate {
+                payer: ctx.accounts.authority.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                thread: ctx.accounts.thread.to_account_info(),
+                authority: collection.to_account_info(),
+            },
+            &[&collection.signer_seeds()],
+        ),
+        args.funding,
+        collection.thread_id.clone(),
+        vec![switch_collection_instruction(collection.key(), collection, None, &[]).into()],
+        Trigger::Cron {
+            schedule: collection.schedule.clone(),
+            skippable: true,
+        },
+    )
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use clockwork_sdk::ThreadProgram;
+
+use crate::errors::NightFuryError;
+use crate::scheduler::SchedulerAccounts;
+use crate::state::NightFury;
+
+#[derive(Accounts)]
+pub struct FundThread<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(has_one = mint, has_one = thread, has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    /// CHECK: matched against the config, only exists for Clockwork configs.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_fund_thread(ctx: Context<FundThread>, amount: u64) -> Result<()> {
+    require!(amount > 0, NightFuryError::InvalidAmount);
+
+    let nightfury = &ctx.accounts.nightfury;
+    nightfury.scheduler.backend().fund(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        amount,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions, system_program};
+use anchor_spl::token::{Mint, TokenAccount};
+use mpl_token_metadata::{
+    state::{Metadata, TokenMetadataAccount},
+    utils::assert_owned_by,
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::UpdateMetadata;
+use crate::state::{NightFury, NightFuryStatus};
+
+#[derive(Accounts)]
+pub struct HolderSwitch<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    #[account(
+        constraint = token_account.mint == mint.key()
+            && token_account.owner == holder.key()
+            && token_account.amount == 1 @ NightFuryError::InvalidHolder
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: receives the switch fee, checked against the config.
+    #[account(mut, address = nightfury.authority @ NightFuryError::InvalidAuthority)]
+    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        has_one = mint,
+        seeds = [
+            b"nightfury".as_ref(),
+            mint.key().as_ref(),
+            nightfury.authority.key().as_ref(),
+            nightfury.thread_id.as_ref()
+        ],
+        bump = nightfury.bump
+    )]
+    pub nightfury: Box<Account<'info, NightFury>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: make sure this is a valid metadata account and that it belongs to the mint.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: make sure the master edition account matches the mint and metadata accounts.
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the correct delegate record.
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: Make sure this account belongs to the auth rules program
+    pub auth_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real token metadata program.
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Shows `phase`, or the phase after the one showing when `None`, until the schedule next
+/// moves on.
+pub fn process_holder_switch(ctx: Context<HolderSwitch>, phase: Option<u8>) -> Result<()> {
+    assert_owned_by(&ctx.accounts.metadata, &mpl_token_metadata::id())?;
+    let metadata = Metadata::from_account_info(&ctx.accounts.metadata)?;
+    require!(
+        metadata.mint == ctx.accounts.mint.key(),
+        NightFuryError::InvalidMint
+    );
+    require!(
+        instructions::check_id(&ctx.accounts.instructions_sysvar.key()),
+        NightFuryError::InvalidInstructionsSysvarId
+    );
+
+    let nightfury = &mut ctx.accounts.nightfury;
+    let phase = nightfury.pick_phase(phase, Clock::get()?.unix_timestamp)?;
+    let fee = nightfury
+        .holder_switch
+        .as_ref()
+        .map_or(0, |config| config.fee);
+    if fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.holder.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    let phase = nightfury.phases[phase as usize].clone();
+    if phase.is_applied(&metadata.data) {
+        msg!("metadata already up to date");
+        return Ok(());
+    }
+
+    let nightfury = &ctx.accounts.nightfury;
+    UpdateMetadata {
+        payer: &ctx.accounts.holder.to_account_info(),
+        nightfury: &nightfury.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.auth_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke(phase.apply(metadata.data), &nightfury.signer_seeds())?;
+
+    Ok(())
+}
+
+impl NightFury {
+    /// Records the holder's pick at unix time `now`: `phase`, or the phase after the one
+    /// showing when `None`. Returns the phase to show.
+    pub fn pick_phase(&mut self, phase: Option<u8>, now: i64) -> Result<u8> {
+        let config = self
+            .holder_switch
+            .clone()
+            .ok_or(NightFuryError::HolderSwitchDisabled)?;
+        require!(!self.mode.is_one_way(), NightFuryError::OneWayMode);
+        // Holders can't switch around a pause any more than the schedule can.
+        require!(
+            self.status == NightFuryStatus::Active,
+            NightFuryError::NotActive
+        );
+        require!(
+            !self.pin.iter().any(|pin| pin.is_active(now)),
+            NightFuryError::PhasePinned
+        );
+        require!(
+            now >= self.manual_switched_at.saturating_add(config.cooldown),
+            NightFuryError::CooldownActive
+        );
+
+        let phase_count = self.phases.len() as u8;
+        let phase = match phase {
+            Some(phase) => {
+                require!(phase < phase_count, NightFuryError::InvalidPhaseIndex);
+                phase
+            }
+            None => (self.manual_phase.unwrap_or(self.phase) + 1) % phase_count,
+        };
+        self.manual_phase = Some(phase);
+        self.manual_switched_at = now;
+
+        Ok(phase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{test_config, HolderSwitchConfig, PhaseMode, Pin};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn holder_config(phase_count: usize) -> NightFury {
+        NightFury {
+            holder_switch: Some(HolderSwitchConfig {
+                cooldown: 600,
+                fee: 0,
+            }),
+            ..test_config(phase_count)
+        }
+    }
+
+    #[test]
+    fn picks_the_given_or_next_phase() {
+        let mut nightfury = holder_config(3);
+        assert_eq!(nightfury.pick_phase(Some(2), NOW).unwrap(), 2);
+        assert_eq!(nightfury.manual_phase, Some(2));
+        assert_eq!(nightfury.manual_switched_at, NOW);
+        // Stepping on goes from the holder's last pick and wraps around.
+        assert_eq!(nightfury.pick_phase(None, NOW + 600).unwrap(), 0);
+        assert_eq!(nightfury.pick_phase(None, NOW + 1_200).unwrap(), 1);
+    }
+
+    #[test]
+    fn rejects_switches_inside_the_cooldown() {
+        let mut nightfury = holder_config(2);
+        nightfury.pick_phase(Some(1), NOW).unwrap();
+        assert_eq!(
+            nightfury.pick_phase(Some(0), NOW + 599).unwrap_err(),
+            NightFuryError::CooldownActive.into()
+        );
+        assert_eq!(nightfury.manual_phase, Some(1));
+        assert_eq!(nightfury.pick_phase(Some(0), NOW + 600).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_phases_out_of_range() {
+        let mut nightfury = holder_config(2);
+        assert_eq!(
+            nightfury.pick_phase(Some(2), NOW).unwrap_err(),
+            NightFuryError::InvalidPhaseIndex.into()
+        );
+        assert_eq!(nightfury.manual_phase, None);
+    }
+
+    #[test]
+    fn leaves_the_schedule_alone_when_it_can_not_switch() {
+        let mut disabled = test_config(2);
+        let mut paused = NightFury {
+            status: NightFuryStatus::Paused,
+            ..holder_config(2)
+        };
+        let mut pinned = NightFury {
+            pin: Some(Pin {
+                phase: 0,
+                until: NOW + 1,
+            }),
+            ..holder_config(2)
+        };
+        let mut evolving = NightFury {
+            mode: PhaseMode::Evolution {
+                stage_duration: 3600,
+            },
+            ..holder_config(2)
+        };
+        for (nightfury, error) in [
+            (&mut disabled, NightFuryError::HolderSwitchDisabled),
+            (&mut paused, NightFuryError::NotActive),
+            (&mut pinned, NightFuryError::PhasePinned),
+            (&mut evolving, NightFuryError::OneWayMode),
+        ] {
+            assert_eq!(
+                nightfury.pick_phase(Some(1), NOW).unwrap_err(),
+                error.into()
+            );
+            assert_eq!(nightfury.manual_phase, None);
+        }
+
+        // Once the pin runs out the holder can pick again.
+        assert_eq!(pinned.pick_phase(Some(1), NOW + 1).unwrap(), 1);
+    }
+}
@@ -1,16 +1,12 @@
-use crate::{errors::NightFuryError, state::NightFuryState};
+use crate::errors::NightFuryError;
+use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
-use anchor_lang::solana_program::program::invoke;
 use anchor_lang::InstructionData;
-use anchor_lang::{prelude::*, solana_program::native_token::LAMPORTS_PER_SOL};
 use anchor_spl::{
     token::Mint,
     token::{Token, TokenAccount},
 };
 use clockwork_sdk::{state::Thread, ThreadProgram};
-use mpl_token_metadata::instruction::builders::DelegateBuilder;
-use mpl_token_metadata::instruction::DelegateArgs;
-use mpl_token_metadata::instruction::InstructionBuilder;
 use mpl_token_metadata::pda::find_metadata_delegate_record_account;
 use mpl_token_metadata::state::MasterEditionV2;
 use mpl_token_metadata::{
@@ -19,14 +15,38 @@ use mpl_token_metadata::{
     utils::assert_owned_by,
 };
 
-use crate::state::NightFury;
+use crate::metaplex::{DelegateMetadata, UpdateMetadata};
+use crate::scheduler::{cron, SchedulerAccounts};
+use crate::state::{
+    Expiry, HolderSwitchConfig, NightFury, NightFuryStatus, Overrides, Phase, PhaseMode, Scheduler,
+    ThreadConfig, Timezone,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InitializeArgs {
+    pub phases: Vec<Phase>,
+    pub mode: PhaseMode,
+    pub timezone: Timezone,
+    /// Stop switching at a given time or after a number of phase changes.
+    pub expiry: Option<Expiry>,
+    /// Let holders switch phases themselves, `None` to keep it to the schedule.
+    pub holder_switch: Option<HolderSwitchConfig>,
+    pub scheduler: Scheduler,
+    /// Cron schedule `switch` is cranked on, seconds through years, in UTC.
+    pub schedule: String,
+    /// Lamports the scheduler starts out with: the thread's fee balance, or the escrow crank
+    /// bounties are paid from.
+    pub funding: u64,
+    /// Clockwork thread settings, `None` for the thread program's defaults.
+    pub thread_config: Option<ThreadConfig>,
+}
 
 #[derive(Accounts)]
-#[instruction(thread_id: Vec<u8>)]
+#[instruction(thread_id: Vec<u8>, args: InitializeArgs)]
 pub struct Initialize<'info> {
     #[account(
         init_if_needed,
-        space = NightFury::LENGTH,
+        space = NightFury::space(args.phases.len()),
         payer = authority,
         seeds = [
             b"nightfury".as_ref(),
@@ -54,6 +74,8 @@ pub struct Initialize<'info> {
     /// CHECK: make sure it's a valid thread
     #[account(mut, address = Thread::pubkey(nightfury.key(), thread_id))]
     pub thread: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
     /// CHECK: Make sure it's the authorization_rules_program
     pub authorization_rules: UncheckedAccount<'info>,
     /// CHECK: Make sure it's the real instructions sysvar.
@@ -70,8 +92,7 @@ pub struct Initialize<'info> {
 pub fn process_initialize(
     ctx: Context<Initialize>,
     thread_id: Vec<u8>,
-    day_uri: String,
-    night_uri: String,
+    args: InitializeArgs,
 ) -> Result<()> {
     msg!("Initializing NightFury");
 
@@ -105,20 +126,31 @@ pub fn process_initialize(
         ctx.accounts.mint.key() == ctx.accounts.token_account.mint,
         NightFuryError::InvalidMint,
     );
-    require!(
-        day_uri.len() <= NightFury::MAX_URI_LENGTH.into(),
-        NightFuryError::UriTooLong
-    );
-    require!(
-        night_uri.len() <= NightFury::MAX_URI_LENGTH.into(),
-        NightFuryError::UriTooLong
-    );
+    NightFury::validate_phases(&args.phases)?;
+    args.mode.validate(args.phases.len())?;
+    args.timezone.validate()?;
+    let now = Clock::get()?.unix_timestamp;
+    cron::validate(&args.schedule, now)?;
+    if let Some(expiry) = &args.expiry {
+        expiry.validate(args.phases.len(), now)?;
+    }
+    if let Some(holder_switch) = &args.holder_switch {
+        // Holders pick any phase, including ones a one-way mode has moved past.
+        require!(!args.mode.is_one_way(), NightFuryError::OneWayMode);
+        holder_switch.validate()?;
+    }
+    if let Some(thread_config) = &args.thread_config {
+        require!(
+            args.scheduler == Scheduler::Clockwork,
+            NightFuryError::UnsupportedScheduler
+        );
+        thread_config.validate()?;
+    }
 
     let authority = &ctx.accounts.authority;
     let mint = &ctx.accounts.mint;
     let metadata_account = &ctx.accounts.metadata;
     let thread = &ctx.accounts.thread.key();
-    let thread_program = &ctx.accounts.thread_program;
     let token_program = &ctx.accounts.token_program;
     let token_metadata_program = &ctx.accounts.token_metadata_program;
     let system_program = &ctx.accounts.system_program;
@@ -127,45 +159,25 @@ pub fn process_initialize(
         &mint.key(),
         MetadataDelegateRole::DataItem,
         &authority.key(),
-        &thread.key(),
+        &ctx.accounts.nightfury.key(),
     );
 
     // Delegate metadata update authorization to nightfury account.
-    let delegate_args = DelegateArgs::DataItemV1 {
-        authorization_data: None,
-    };
-    let delegate_instruction = DelegateBuilder::new()
-        .delegate(thread.key())
-        .metadata(metadata_account.key())
-        .master_edition(ctx.accounts.master_edition.key())
-        .authority(authority.key())
-        .payer(ctx.accounts.authority.key())
-        .mint(ctx.accounts.mint.key())
-        .token(ctx.accounts.token_account.key())
-        .spl_token_program(token_program.key())
-        .delegate_record(delegate_record_address)
-        .authorization_rules(ctx.accounts.authorization_rules.key())
-        .authorization_rules_program(mpl_token_auth_rules::ID)
-        .system_program(ctx.accounts.system_program.key())
-        .build(delegate_args)
-        .unwrap()
-        .instruction();
-    let delegate_account_infos = vec![
-        ctx.accounts.delegate_record.to_account_info(),
-        ctx.accounts.thread.to_account_info(),
-        ctx.accounts.metadata.to_account_info(),
-        ctx.accounts.master_edition.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.token_account.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.instructions_sysvar.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.authorization_rules_program.to_account_info(),
-        ctx.accounts.authorization_rules.to_account_info(),
-    ];
-    invoke(&delegate_instruction, delegate_account_infos.as_slice())?;
+    DelegateMetadata {
+        authority: &authority.to_account_info(),
+        nightfury: &ctx.accounts.nightfury.to_account_info(),
+        mint: &mint.to_account_info(),
+        token_account: &ctx.accounts.token_account.to_account_info(),
+        metadata: &metadata_account.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &token_program.to_account_info(),
+        system_program: &system_program.to_account_info(),
+    }
+    .invoke()?;
 
     // Create target instruction.
     let switch_instruction = Instruction {
@@ -173,10 +185,13 @@ pub fn process_initialize(
         accounts: crate::accounts::Switch {
             auth_rules: ctx.accounts.authorization_rules.key(),
             nightfury: ctx.accounts.nightfury.key(),
+            overrides: Overrides::pubkey(ctx.accounts.nightfury.key()),
             mint: mint.key(),
             delegate_record: delegate_record_address,
             metadata: metadata_account.key(),
-            thread: thread.key(),
+            signer: thread.key(),
+            escrow: NightFury::escrow_pubkey(ctx.accounts.nightfury.key()),
+            oracle: args.mode.oracle(),
             token_metadata_program: token_metadata_program.key(),
             instructions_sysvar: ctx.accounts.instructions_sysvar.key(),
             authorization_rules_program: ctx.accounts.authorization_rules_program.key(),
@@ -188,46 +203,69 @@ pub fn process_initialize(
         data: crate::instruction::Switch {}.data(),
     };
 
-    // Create the thread.
-    let trigger = clockwork_sdk::state::Trigger::Cron {
-        // schedule: "*/30 * * * * * *".into(),
-        schedule: "00 00 */12 * * * *".into(),
-        skippable: true,
-    };
-    let nightfury_bump = *ctx.bumps.get("nightfury").unwrap();
-    clockwork_sdk::cpi::thread_create(
-        CpiContext::new_with_signer(
-            thread_program.to_account_info(),
-            clockwork_sdk::cpi::ThreadCreate {
-                payer: ctx.accounts.authority.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                thread: ctx.accounts.thread.to_account_info(),
-                authority: ctx.accounts.nightfury.to_owned().to_account_info(),
-            },
-            &[&[
-                b"nightfury".as_ref(),
-                ctx.accounts.mint.key().as_ref(),
-                ctx.accounts.authority.key().as_ref(),
-                thread_id.clone().as_ref(),
-                &[nightfury_bump],
-            ]],
-        ),
-        LAMPORTS_PER_SOL,
-        thread_id.clone(),
-        vec![switch_instruction.into()],
-        trigger,
-    )?;
-
     // Initialize nightfury account.
+    let placeholder = args.phases[0].clone();
     let nightfury = &mut ctx.accounts.nightfury;
     nightfury.thread = ctx.accounts.thread.key();
     nightfury.authority = ctx.accounts.authority.key();
     nightfury.mint = ctx.accounts.mint.key();
-    nightfury.day_uri = day_uri;
-    nightfury.night_uri = night_uri;
-    nightfury.state = NightFuryState::Day;
+    nightfury.phase = 0;
+    nightfury.phase_changed_at = now;
+    nightfury.phases = args.phases;
+    nightfury.mode = args.mode;
+    nightfury.timezone = args.timezone;
+    nightfury.status = NightFuryStatus::Active;
+    nightfury.expiry = args.expiry;
+    nightfury.switch_count = 0;
+    nightfury.holder_switch = args.holder_switch;
+    nightfury.manual_phase = None;
+    nightfury.manual_switched_at = 0;
+    nightfury.pin = None;
+    nightfury.scheduler = args.scheduler;
+    nightfury.next_switch_at =
+        cron::next_fire(&args.schedule, now).ok_or(NightFuryError::ScheduleExhausted)?;
+    nightfury.schedule = args.schedule;
+    nightfury.thread_config = args.thread_config.unwrap_or_default();
     nightfury.thread_id = thread_id;
-    nightfury.bump = nightfury_bump;
+    nightfury.bump = *ctx.bumps.get("nightfury").unwrap();
+
+    // Start whichever scheduler drives the config.
+    let nightfury = &ctx.accounts.nightfury;
+    nightfury.scheduler.backend().start(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        switch_instruction,
+        args.funding,
+    )?;
+
+    // Reveals show their placeholder from the start rather than whatever was minted.
+    if matches!(nightfury.mode, PhaseMode::Reveal { .. }) && !placeholder.is_applied(&metadata.data)
+    {
+        UpdateMetadata {
+            payer: &ctx.accounts.authority.to_account_info(),
+            nightfury: &nightfury.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            master_edition: &ctx.accounts.master_edition.to_account_info(),
+            delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+            auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+            authorization_rules_program: &ctx
+                .accounts
+                .authorization_rules_program
+                .to_account_info(),
+            instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        }
+        .invoke(placeholder.apply(metadata.data), &nightfury.signer_seeds())?;
+    }
 
     Ok(())
 }
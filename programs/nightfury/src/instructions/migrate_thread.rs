@@ -0,0 +1,219 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction, InstructionData};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use clockwork_sdk::{state::Thread, ThreadProgram};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::{DelegateMetadata, RevokeDelegate};
+use crate::scheduler::SchedulerAccounts;
+use crate::state::{NightFury, NightFuryStatus, Overrides, Scheduler};
+use crate::system::create_pda;
+
+#[derive(Accounts)]
+#[instruction(thread_id: Vec<u8>)]
+pub struct MigrateThread<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, close = authority, has_one = thread, has_one = mint, has_one = authority)]
+    pub nightfury: Box<Account<'info, NightFury>>,
+    #[account(
+        init,
+        space = NightFury::space(nightfury.phases.len()),
+        payer = authority,
+        seeds = [
+            b"nightfury".as_ref(),
+            mint.key().as_ref(),
+            authority.key().as_ref(),
+            NightFury::thread_id_seed(&thread_id)?
+        ],
+        bump
+    )]
+    pub new_nightfury: Box<Account<'info, NightFury>>,
+    /// CHECK: only exists once an override has been added, moved over when it does.
+    #[account(mut, seeds = [b"overrides".as_ref(), nightfury.key().as_ref()], bump)]
+    pub overrides: UncheckedAccount<'info>,
+    /// CHECK: created here when there are overrides to move over.
+    #[account(mut, seeds = [b"overrides".as_ref(), new_nightfury.key().as_ref()], bump)]
+    pub new_overrides: UncheckedAccount<'info>,
+    /// CHECK: matched against the config, only exists for Clockwork configs.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    /// CHECK: created by the thread program for Clockwork configs.
+    #[account(mut, address = Thread::pubkey(new_nightfury.key(), thread_id))]
+    pub new_thread: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), new_nightfury.key().as_ref()], bump)]
+    pub new_escrow: SystemAccount<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+    pub token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: checked by the token metadata program when moving the delegate.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by the token metadata program when moving the delegate.
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: the old config's delegate record, closed by the revoke.
+    #[account(mut)]
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: the new config's delegate record, created by the delegate.
+    #[account(mut)]
+    pub new_delegate_record: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the authorization_rules_program
+    pub authorization_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the real instructions sysvar.
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Make sure this is the real token metadata program.
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves a config onto a fresh thread and PDA under `thread_id`, e.g. to recover from a
+/// broken or underfunded thread. Everything but the thread carries over, and the old
+/// scheduler's balance is refunded to the authority while the new one starts with `funding`.
+pub fn process_migrate_thread(
+    ctx: Context<MigrateThread>,
+    thread_id: Vec<u8>,
+    funding: u64,
+) -> Result<()> {
+    let nightfury = &ctx.accounts.nightfury;
+    require!(
+        nightfury.status != NightFuryStatus::Finished,
+        NightFuryError::AlreadyFinished
+    );
+    // A batch lists its members by PDA and would lose track of the new one, so batched
+    // configs have to leave their batch first.
+    require!(
+        !matches!(nightfury.scheduler, Scheduler::Batch { .. }),
+        NightFuryError::UnsupportedScheduler
+    );
+
+    // Tear down the old scheduler and move the delegate over to the new config.
+    nightfury.scheduler.backend().stop(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+    )?;
+    RevokeDelegate {
+        authority: &ctx.accounts.authority.to_account_info(),
+        nightfury: &nightfury.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke()?;
+    DelegateMetadata {
+        authority: &ctx.accounts.authority.to_account_info(),
+        nightfury: &ctx.accounts.new_nightfury.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        token_account: &ctx.accounts.token_account.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.new_delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke()?;
+
+    // Carry the overrides over to the new config, if there are any.
+    let overrides = &ctx.accounts.overrides;
+    if !overrides.data_is_empty() {
+        let overrides = Account::<Overrides>::try_from(overrides)?;
+        let new_overrides = &ctx.accounts.new_overrides;
+        let new_overrides_bump = *ctx.bumps.get("new_overrides").unwrap();
+        create_pda(
+            &ctx.accounts.authority.to_account_info(),
+            &new_overrides.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Overrides::LENGTH,
+            &[
+                b"overrides".as_ref(),
+                ctx.accounts.new_nightfury.key().as_ref(),
+                &[new_overrides_bump],
+            ],
+        )?;
+        Overrides {
+            nightfury: ctx.accounts.new_nightfury.key(),
+            entries: overrides.entries.clone(),
+            bump: new_overrides_bump,
+        }
+        .try_serialize(&mut &mut new_overrides.try_borrow_mut_data()?[..])?;
+        overrides.close(ctx.accounts.authority.to_account_info())?;
+    }
+
+    // Copy the config over, only the thread changes.
+    let new_nightfury_key = ctx.accounts.new_nightfury.key();
+    let switch_instruction = Instruction {
+        program_id: crate::id(),
+        accounts: crate::accounts::Switch {
+            auth_rules: ctx.accounts.authorization_rules.key(),
+            nightfury: new_nightfury_key,
+            overrides: ctx.accounts.new_overrides.key(),
+            mint: ctx.accounts.mint.key(),
+            delegate_record: ctx.accounts.new_delegate_record.key(),
+            metadata: ctx.accounts.metadata.key(),
+            signer: ctx.accounts.new_thread.key(),
+            escrow: ctx.accounts.new_escrow.key(),
+            oracle: nightfury.mode.oracle(),
+            token_metadata_program: ctx.accounts.token_metadata_program.key(),
+            instructions_sysvar: ctx.accounts.instructions_sysvar.key(),
+            authorization_rules_program: ctx.accounts.authorization_rules_program.key(),
+            token_program: ctx.accounts.token_program.key(),
+            system_program: ctx.accounts.system_program.key(),
+            master_edition: ctx.accounts.master_edition.key(),
+        }
+        .to_account_metas(Some(true)),
+        data: crate::instruction::Switch {}.data(),
+    };
+    let state = NightFury::clone(nightfury);
+    ctx.accounts.new_nightfury.set_inner(NightFury {
+        thread: ctx.accounts.new_thread.key(),
+        thread_id,
+        bump: *ctx.bumps.get("new_nightfury").unwrap(),
+        ..state
+    });
+
+    // Start the new scheduler, leaving it paused if the config was.
+    let new_nightfury = &ctx.accounts.new_nightfury;
+    let scheduler_accounts = SchedulerAccounts {
+        nightfury: new_nightfury.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info(),
+        thread: ctx.accounts.new_thread.to_account_info(),
+        thread_program: ctx.accounts.thread_program.to_account_info(),
+        escrow: ctx.accounts.new_escrow.to_account_info(),
+        escrow_bump: *ctx.bumps.get("new_escrow").unwrap(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    let backend = new_nightfury.scheduler.backend();
+    backend.start(
+        new_nightfury,
+        &scheduler_accounts,
+        switch_instruction,
+        funding,
+    )?;
+    if new_nightfury.status == NightFuryStatus::Paused {
+        backend.pause(new_nightfury, &scheduler_accounts)?;
+    }
+
+    Ok(())
+}
@@ -1,13 +1,63 @@
+pub mod add_override;
+pub mod add_to_batch;
+pub mod batch_register;
+pub mod close_batch;
+pub mod close_collection;
+pub mod configure_thread;
+pub mod create_batch;
+pub mod create_collection;
+pub mod fund_thread;
+pub mod holder_switch;
 pub mod initialize;
+pub mod migrate_thread;
+pub mod pause;
+pub mod pause_collection;
+pub mod pin_phase;
+pub mod register_member;
+pub mod remove_from_batch;
+pub mod remove_member;
+pub mod remove_override;
+pub mod resume;
+pub mod resume_collection;
 pub mod revoke;
+pub mod revoke_legacy;
 pub mod switch;
+pub mod switch_batch;
+pub mod switch_collection;
 pub mod update;
-pub mod update_day_uri;
-pub mod update_night_uri;
+pub mod update_collection_phase;
+pub mod update_collection_schedule;
+pub mod update_phase;
+pub mod withdraw_thread;
 
+pub use add_override::*;
+pub use add_to_batch::*;
+pub use batch_register::*;
+pub use close_batch::*;
+pub use close_collection::*;
+pub use configure_thread::*;
+pub use create_batch::*;
+pub use create_collection::*;
+pub use fund_thread::*;
+pub use holder_switch::*;
 pub use initialize::*;
+pub use migrate_thread::*;
+pub use pause::*;
+pub use pause_collection::*;
+pub use pin_phase::*;
+pub use register_member::*;
+pub use remove_from_batch::*;
+pub use remove_member::*;
+pub use remove_override::*;
+pub use resume::*;
+pub use resume_collection::*;
 pub use revoke::*;
+pub use revoke_legacy::*;
 pub use switch::*;
+pub use switch_batch::*;
+pub use switch_collection::*;
 pub use update::*;
-pub use update_day_uri::*;
-pub use update_night_uri::*;
+pub use update_collection_phase::*;
+pub use update_collection_schedule::*;
+pub use update_phase::*;
+pub use withdraw_thread::*;
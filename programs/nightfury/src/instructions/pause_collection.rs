@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_pause, ThreadPause},
+    ThreadProgram,
+};
+
+use crate::state::Collection;
+
+#[derive(Accounts)]
+pub struct PauseCollection<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, has_one = thread)]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: matched against the collection.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+}
+
+/// Stops switching every member until `resume_collection`. Members keep their delegates and
+/// whatever phase they're showing, and a run in flight stops where it is.
+pub fn process_pause_collection(ctx: Context<PauseCollection>) -> Result<()> {
+    let collection = &ctx.accounts.collection;
+    thread_pause(CpiContext::new_with_signer(
+        ctx.accounts.thread_program.to_account_info(),
+        ThreadPause {
+            authority: collection.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+        },
+        &[&collection.signer_seeds()],
+    ))
+}
@@ -0,0 +1,218 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::token::Mint;
+use mpl_token_metadata::{
+    state::{Metadata, TokenMetadataAccount},
+    utils::assert_owned_by,
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::UpdateMetadata;
+use crate::state::{NightFury, NightFuryStatus, PhaseMode, Pin};
+
+#[derive(Accounts)]
+pub struct PinPhase<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = mint, has_one = authority)]
+    pub nightfury: Box<Account<'info, NightFury>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: make sure this is a valid metadata account and that it belongs to the mint.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: make sure the master edition account matches the mint and metadata accounts.
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the correct delegate record.
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: Make sure this account belongs to the auth rules program
+    pub auth_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real token metadata program.
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Shows `phase` right away and keeps scheduled and holder switches off it until `until`.
+pub fn process_pin_phase(ctx: Context<PinPhase>, phase: u8, until: i64) -> Result<()> {
+    assert_owned_by(&ctx.accounts.metadata, &mpl_token_metadata::id())?;
+    let metadata = Metadata::from_account_info(&ctx.accounts.metadata)?;
+    require!(
+        metadata.mint == ctx.accounts.mint.key(),
+        NightFuryError::InvalidMint
+    );
+    require!(
+        instructions::check_id(&ctx.accounts.instructions_sysvar.key()),
+        NightFuryError::InvalidInstructionsSysvarId
+    );
+
+    let nightfury = &mut ctx.accounts.nightfury;
+    nightfury.pin_phase(phase, until, Clock::get()?.unix_timestamp)?;
+
+    let phase = nightfury.phases[phase as usize].clone();
+    if phase.is_applied(&metadata.data) {
+        msg!("metadata already up to date");
+        return Ok(());
+    }
+
+    let nightfury = &ctx.accounts.nightfury;
+    UpdateMetadata {
+        payer: &ctx.accounts.authority.to_account_info(),
+        nightfury: &nightfury.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.auth_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke(phase.apply(metadata.data), &nightfury.signer_seeds())?;
+
+    Ok(())
+}
+
+impl NightFury {
+    /// Pins `phase` from unix time `now` until `until`.
+    pub fn pin_phase(&mut self, phase: u8, until: i64, now: i64) -> Result<()> {
+        // Pins are the authority's own call, so unlike holder switches they go through while
+        // the config is paused.
+        require!(
+            self.status != NightFuryStatus::Finished,
+            NightFuryError::AlreadyFinished
+        );
+        require!(
+            (phase as usize) < self.phases.len(),
+            NightFuryError::InvalidPhaseIndex
+        );
+        require!(until > now, NightFuryError::InvalidPinWindow);
+        // One-way modes can only be pinned forward, and carry on from the pin once it runs out
+        // rather than falling back to where they were.
+        if let PhaseMode::Reveal { reveal_at } = self.mode {
+            require!(phase == 0 || now >= reveal_at, NightFuryError::RevealNotDue);
+        }
+        if self.mode.is_one_way() {
+            require!(
+                phase >= self.target_phase(now, None)?,
+                NightFuryError::OneWayMode
+            );
+            if phase != self.phase {
+                self.phase = phase;
+                self.phase_changed_at = now;
+            }
+        }
+        // The schedule picks up where it should be once the pin runs out, not at whatever
+        // the holder last chose.
+        self.manual_phase = None;
+        self.manual_switched_at = 0;
+        self.pin = Some(Pin { phase, until });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::Showing;
+    use crate::state::{test_config, Expiry};
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn pins_hold_over_the_schedule_and_expiry_until_they_run_out() {
+        let mut nightfury = NightFury {
+            expiry: Some(Expiry {
+                end_at: Some(NOW + 50),
+                max_switches: None,
+                final_phase: 0,
+            }),
+            ..test_config(3)
+        };
+        nightfury.pin_phase(2, NOW + 100, NOW).unwrap();
+        assert_eq!(nightfury.advance(1, NOW + 60), Showing::Pinned(2));
+        assert_eq!(nightfury.advance(0, NOW + 99), Showing::Pinned(2));
+        assert_eq!(nightfury.status, NightFuryStatus::Active);
+
+        // The expiry that passed under the pin takes over once it runs out.
+        assert_eq!(nightfury.advance(1, NOW + 100), Showing::Final(0));
+    }
+
+    #[test]
+    fn the_schedule_resumes_without_the_holder_pick() {
+        let mut nightfury = test_config(3);
+        nightfury.manual_phase = Some(2);
+        nightfury.manual_switched_at = NOW - 10;
+        nightfury.pin_phase(0, NOW + 100, NOW).unwrap();
+        assert_eq!(nightfury.manual_phase, None);
+        assert_eq!(nightfury.manual_switched_at, 0);
+
+        // Still on the same scheduled phase the pick was made during.
+        assert_eq!(nightfury.advance(0, NOW + 100), Showing::Scheduled(0));
+    }
+
+    #[test]
+    fn validates_the_pin() {
+        let mut finished = NightFury {
+            status: NightFuryStatus::Finished,
+            ..test_config(2)
+        };
+        assert_eq!(
+            finished.pin_phase(0, NOW + 1, NOW).unwrap_err(),
+            NightFuryError::AlreadyFinished.into()
+        );
+
+        let mut nightfury = test_config(2);
+        assert_eq!(
+            nightfury.pin_phase(2, NOW + 1, NOW).unwrap_err(),
+            NightFuryError::InvalidPhaseIndex.into()
+        );
+        assert_eq!(
+            nightfury.pin_phase(1, NOW, NOW).unwrap_err(),
+            NightFuryError::InvalidPinWindow.into()
+        );
+        assert!(nightfury.pin.is_none());
+
+        // Paused configs can still be pinned.
+        nightfury.status = NightFuryStatus::Paused;
+        nightfury.pin_phase(1, NOW + 1, NOW).unwrap();
+        assert_eq!(
+            nightfury.pin,
+            Some(Pin {
+                phase: 1,
+                until: NOW + 1
+            })
+        );
+    }
+
+    #[test]
+    fn one_way_modes_are_only_pinned_forward() {
+        let mut reveal = NightFury {
+            mode: PhaseMode::Reveal {
+                reveal_at: NOW + 10,
+            },
+            ..test_config(2)
+        };
+        assert_eq!(
+            reveal.pin_phase(1, NOW + 100, NOW).unwrap_err(),
+            NightFuryError::RevealNotDue.into()
+        );
+        reveal.pin_phase(0, NOW + 100, NOW).unwrap();
+
+        let mut evolution = NightFury {
+            mode: PhaseMode::Evolution { stage_duration: 10 },
+            phase: 1,
+            phase_changed_at: NOW,
+            ..test_config(3)
+        };
+        assert_eq!(
+            evolution.pin_phase(0, NOW + 100, NOW).unwrap_err(),
+            NightFuryError::OneWayMode.into()
+        );
+        evolution.pin_phase(2, NOW + 100, NOW).unwrap();
+        assert_eq!(evolution.phase, 2);
+        assert_eq!(evolution.phase_changed_at, NOW);
+    }
+}
@@ -0,0 +1,177 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use mpl_token_metadata::{
+    state::{MasterEditionV2, Metadata, TokenMetadataAccount},
+    utils::assert_owned_by,
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::{DelegateMetadata, UpdateMetadata};
+use crate::state::{Collection, CollectionMember, PhaseMode};
+
+#[derive(Accounts)]
+pub struct RegisterMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(
+        init,
+        space = CollectionMember::LENGTH,
+        payer = authority,
+        seeds = [
+            b"member".as_ref(),
+            collection.key().as_ref(),
+            collection.next_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub member: Box<Account<'info, CollectionMember>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: checked in `delegate_member`.
+    pub token_account: UncheckedAccount<'info>,
+    /// CHECK: checked in `delegate_member`.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: checked in `delegate_member`.
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: assert derivation and owner of this account.
+    #[account(mut)]
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: matched against the collection's.
+    pub authorization_rules: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_register_member(ctx: Context<RegisterMember>) -> Result<()> {
+    let collection = &ctx.accounts.collection;
+    delegate_member(
+        collection,
+        &MemberAccounts {
+            authority: &ctx.accounts.authority.to_account_info(),
+            collection: &collection.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            token_account: &ctx.accounts.token_account.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            master_edition: &ctx.accounts.master_edition.to_account_info(),
+            delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+            auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+            authorization_rules_program: &ctx
+                .accounts
+                .authorization_rules_program
+                .to_account_info(),
+            instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        },
+    )?;
+
+    let collection = &mut ctx.accounts.collection;
+    let member = &mut ctx.accounts.member;
+    member.collection = collection.key();
+    member.mint = ctx.accounts.mint.key();
+    member.index = collection.next_index;
+    member.bump = *ctx.bumps.get("member").unwrap();
+    collection.next_index += 1;
+    collection.member_count += 1;
+
+    Ok(())
+}
+
+/// Accounts needed to hand one mint's data item delegate to its collection.
+pub struct MemberAccounts<'a, 'info> {
+    pub authority: &'a AccountInfo<'info>,
+    pub collection: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_account: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub delegate_record: &'a AccountInfo<'info>,
+    pub auth_rules: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// Checks a mint the way `initialize` does, plus that it's a verified member of the
+/// collection, and makes the collection PDA its delegate. Registering the same mint twice
+/// fails here, as the delegate record already exists. Reveals show the collection's current
+/// phase straight away, as `initialize` does, rather than the real art until the next run.
+pub fn delegate_member(collection: &Collection, accounts: &MemberAccounts) -> Result<()> {
+    require_keys_eq!(
+        accounts.auth_rules.key(),
+        collection.auth_rules,
+        NightFuryError::InvalidAuthRules
+    );
+
+    assert_owned_by(accounts.metadata, &mpl_token_metadata::id())?;
+    let metadata = Metadata::from_account_info(accounts.metadata)?;
+    assert_owned_by(accounts.master_edition, &mpl_token_metadata::id())?;
+    MasterEditionV2::from_account_info(accounts.master_edition).map_err(|_| {
+        msg!("Not master edition v2");
+        NightFuryError::InvalidEditionAccount
+    })?;
+    require!(
+        metadata.mint == accounts.mint.key(),
+        NightFuryError::InvalidMint
+    );
+    let token_account = Account::<TokenAccount>::try_from(accounts.token_account)?;
+    require!(
+        token_account.mint == accounts.mint.key(),
+        NightFuryError::InvalidMint
+    );
+    require!(
+        metadata
+            .collection
+            .iter()
+            .any(|parent| parent.verified && parent.key == collection.collection_mint),
+        NightFuryError::InvalidCollection
+    );
+
+    DelegateMetadata {
+        authority: accounts.authority,
+        nightfury: accounts.collection,
+        mint: accounts.mint,
+        token_account: accounts.token_account,
+        metadata: accounts.metadata,
+        master_edition: accounts.master_edition,
+        delegate_record: accounts.delegate_record,
+        auth_rules: accounts.auth_rules,
+        authorization_rules_program: accounts.authorization_rules_program,
+        instructions_sysvar: accounts.instructions_sysvar,
+        token_program: accounts.token_program,
+        system_program: accounts.system_program,
+    }
+    .invoke()?;
+
+    let phase = &collection.phases[collection.phase as usize];
+    if matches!(collection.mode, PhaseMode::Reveal { .. }) && !phase.is_applied(&metadata.data) {
+        UpdateMetadata {
+            payer: accounts.authority,
+            nightfury: accounts.collection,
+            mint: accounts.mint,
+            metadata: accounts.metadata,
+            master_edition: accounts.master_edition,
+            delegate_record: accounts.delegate_record,
+            auth_rules: accounts.auth_rules,
+            authorization_rules_program: accounts.authorization_rules_program,
+            instructions_sysvar: accounts.instructions_sysvar,
+            system_program: accounts.system_program,
+        }
+        .invoke(phase.apply(metadata.data), &collection.signer_seeds())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::state::{Batch, BatchMember};
+
+#[derive(Accounts)]
+pub struct RemoveFromBatch<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub batch: Box<Account<'info, Batch>>,
+}
+
+/// Takes the config at `nightfury` out of the batch, whether or not it still exists. Its slot
+/// is left vacant rather than closed up, as a queued page would otherwise shift under a run.
+pub fn process_remove_from_batch(ctx: Context<RemoveFromBatch>, nightfury: Pubkey) -> Result<()> {
+    let batch = &mut ctx.accounts.batch;
+    let index = batch
+        .members
+        .iter()
+        .position(|member| member.nightfury == nightfury)
+        .ok_or(NightFuryError::NotInBatch)?;
+    batch.members[index] = BatchMember::VACANT;
+    // Nothing comes after trailing slots, so those can go.
+    while matches!(batch.members.last(), Some(member) if member.is_vacant()) {
+        batch.members.pop();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
+use anchor_spl::token::{Mint, Token};
+
+use crate::metaplex::RevokeDelegate;
+use crate::state::{Collection, CollectionMember};
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority)]
+    pub collection: Box<Account<'info, Collection>>,
+    #[account(mut, close = authority, has_one = collection, has_one = mint)]
+    pub member: Box<Account<'info, CollectionMember>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: checked by the token metadata program when revoking.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by the token metadata program when revoking.
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: closed by the revoke.
+    #[account(mut)]
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the authorization_rules_program
+    pub authorization_rules: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Takes a mint out of its collection, leaving its metadata as it is. The member's index
+/// stays unused.
+pub fn process_remove_member(ctx: Context<RemoveMember>) -> Result<()> {
+    RevokeDelegate {
+        authority: &ctx.accounts.authority.to_account_info(),
+        nightfury: &ctx.accounts.collection.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke()?;
+
+    ctx.accounts.collection.member_count -= 1;
+
+    Ok(())
+}
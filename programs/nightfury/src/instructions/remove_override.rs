@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::state::{NightFury, Overrides};
+
+#[derive(Accounts)]
+pub struct RemoveOverride<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    #[account(
+        mut,
+        has_one = nightfury,
+        seeds = [b"overrides".as_ref(), nightfury.key().as_ref()],
+        bump = overrides.bump
+    )]
+    pub overrides: Box<Account<'info, Overrides>>,
+}
+
+pub fn process_remove_override(ctx: Context<RemoveOverride>, index: u8) -> Result<()> {
+    let overrides = &mut ctx.accounts.overrides;
+
+    require!(
+        (index as usize) < overrides.entries.len(),
+        NightFuryError::InvalidOverrideIndex
+    );
+    overrides.entries.remove(index as usize);
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_resume, ThreadResume},
+    ThreadProgram,
+};
+
+use crate::state::Collection;
+
+#[derive(Accounts)]
+pub struct ResumeCollection<'info> {
+    pub authority: Signer<'info>,
+    #[account(has_one = authority, has_one = thread)]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: matched against the collection.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+}
+
+pub fn process_resume_collection(ctx: Context<ResumeCollection>) -> Result<()> {
+    let collection = &ctx.accounts.collection;
+    thread_resume(CpiContext::new_with_signer(
+        ctx.accounts.thread_program.to_account_info(),
+        ThreadResume {
+            authority: collection.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+        },
+        &[&collection.signer_seeds()],
+    ))
+}
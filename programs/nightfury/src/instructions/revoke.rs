@@ -1,23 +1,25 @@
-use anchor_lang::{prelude::*, solana_program::program::invoke};
+use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token};
-use clockwork_sdk::{
-    cpi::{thread_delete, ThreadDelete},
-    state::{Thread, ThreadAccount},
-    ThreadProgram,
-};
-use mpl_token_metadata::instruction::builders::RevokeBuilder;
-use mpl_token_metadata::instruction::InstructionBuilder;
-use mpl_token_metadata::instruction::RevokeArgs;
+use clockwork_sdk::ThreadProgram;
 
-use crate::state::NightFury;
+use crate::metaplex::RevokeDelegate;
+use crate::scheduler::SchedulerAccounts;
+use crate::state::{NightFury, Overrides};
 
 #[derive(Accounts)]
 pub struct Revoke<'info> {
+    #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut, has_one = thread, has_one = mint, has_one = authority)]
+    #[account(mut, close = authority, has_one = thread, has_one = mint, has_one = authority)]
     pub nightfury: Account<'info, NightFury>,
-    #[account(mut, address = thread.pubkey(), constraint = thread.authority.eq(&nightfury.key()))]
-    pub thread: Account<'info, Thread>,
+    /// CHECK: matched against the config; already gone if the config expired.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+    /// CHECK: closed along with the config, if the authority ever added overrides.
+    #[account(mut, seeds = [b"overrides".as_ref(), nightfury.key().as_ref()], bump)]
+    pub overrides: UncheckedAccount<'info>,
 
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: make sure this is a valid metadata account and that it belongs to the mint.
@@ -69,55 +71,40 @@ pub fn process_revoke(ctx: Context<Revoke>) -> Result<()> {
     let nightfury = &ctx.accounts.nightfury;
 
     // Revoke the metaplex update authority.
-    let revoke_args = RevokeArgs::DataItemV1 {};
-    let revoke_instruction = RevokeBuilder::new()
-        .delegate_record(ctx.accounts.delegate_record.key())
-        .delegate(ctx.accounts.thread.key())
-        .metadata(ctx.accounts.metadata.key())
-        .master_edition(ctx.accounts.master_edition.key())
-        .mint(ctx.accounts.mint.key())
-        .authority(ctx.accounts.authority.key())
-        .payer(ctx.accounts.authority.key())
-        .system_program(ctx.accounts.system_program.key())
-        .sysvar_instructions(ctx.accounts.instructions_sysvar.key())
-        .spl_token_program(ctx.accounts.token_program.key())
-        .authorization_rules_program(ctx.accounts.authorization_rules_program.key())
-        .authorization_rules(ctx.accounts.authorization_rules.key())
-        .build(revoke_args)
-        .unwrap()
-        .instruction();
-    let revoke_account_infos = vec![
-        ctx.accounts.delegate_record.to_account_info(),
-        ctx.accounts.thread.to_account_info(),
-        ctx.accounts.metadata.to_account_info(),
-        ctx.accounts.master_edition.to_account_info(),
-        ctx.accounts.mint.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.instructions_sysvar.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.authorization_rules_program.to_account_info(),
-        ctx.accounts.authorization_rules.to_account_info(),
-    ];
-    invoke(&revoke_instruction, revoke_account_infos.as_slice())?;
+    RevokeDelegate {
+        authority: &ctx.accounts.authority.to_account_info(),
+        nightfury: &nightfury.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke()?;
 
-    // Delete the thread.
-    thread_delete(CpiContext::new_with_signer(
-        ctx.accounts.thread_program.to_account_info(),
-        ThreadDelete {
-            authority: ctx.accounts.nightfury.to_account_info(),
-            close_to: ctx.accounts.authority.to_account_info(),
+    // Tear down whichever scheduler drives the config.
+    nightfury.scheduler.backend().stop(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
             thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
         },
-        &[&[
-            b"nightfury".as_ref(),
-            ctx.accounts.mint.key().as_ref(),
-            ctx.accounts.authority.key().as_ref(),
-            ctx.accounts.nightfury.thread_id.as_ref(),
-            &[nightfury.bump],
-        ]],
-    ))?;
+    )?;
+
+    let overrides = &ctx.accounts.overrides;
+    if !overrides.data_is_empty() {
+        Account::<Overrides>::try_from(overrides)?
+            .close(ctx.accounts.authority.to_account_info())?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+use clockwork_sdk::{
+    cpi::{thread_delete, ThreadDelete},
+    state::Thread,
+    ThreadProgram,
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::RevokeDelegate;
+use crate::state::LegacyNightFury;
+
+#[derive(Accounts)]
+#[instruction(thread_id: Vec<u8>)]
+pub struct RevokeLegacy<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: read as a legacy config in `process_revoke_legacy`.
+    #[account(
+        mut,
+        seeds = [
+            b"nightfury".as_ref(),
+            mint.key().as_ref(),
+            authority.key().as_ref(),
+            thread_id.as_ref()
+        ],
+        bump
+    )]
+    pub nightfury: UncheckedAccount<'info>,
+    /// CHECK: the config's thread and data delegate; may already be gone.
+    #[account(mut, address = Thread::pubkey(nightfury.key(), thread_id.clone()))]
+    pub thread: UncheckedAccount<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: checked by the token metadata program when revoking.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: checked by the token metadata program when revoking.
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: the thread's delegate record, closed by the revoke.
+    #[account(mut)]
+    pub delegate_record: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the authorization_rules_program
+    pub authorization_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure it's the real instructions sysvar.
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Revokes a config created before phases replaced the day/night toggle, which `revoke` can
+/// no longer read: revokes its thread's delegate, deletes the thread and closes the config.
+pub fn process_revoke_legacy(ctx: Context<RevokeLegacy>, thread_id: Vec<u8>) -> Result<()> {
+    let legacy = LegacyNightFury::try_from_account(&ctx.accounts.nightfury)?;
+    require!(
+        legacy.authority == ctx.accounts.authority.key()
+            && legacy.mint == ctx.accounts.mint.key()
+            && legacy.thread == ctx.accounts.thread.key()
+            && legacy.thread_id == thread_id,
+        NightFuryError::InvalidLegacyConfig
+    );
+
+    // Legacy configs delegated to their thread rather than to themselves.
+    RevokeDelegate {
+        authority: &ctx.accounts.authority.to_account_info(),
+        nightfury: &ctx.accounts.thread.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        metadata: &ctx.accounts.metadata.to_account_info(),
+        master_edition: &ctx.accounts.master_edition.to_account_info(),
+        delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+        auth_rules: &ctx.accounts.authorization_rules.to_account_info(),
+        authorization_rules_program: &ctx.accounts.authorization_rules_program.to_account_info(),
+        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    }
+    .invoke()?;
+
+    let nightfury = ctx.accounts.nightfury.to_account_info();
+    let authority = ctx.accounts.authority.to_account_info();
+    if !ctx.accounts.thread.data_is_empty() {
+        thread_delete(CpiContext::new_with_signer(
+            ctx.accounts.thread_program.to_account_info(),
+            ThreadDelete {
+                authority: nightfury.clone(),
+                close_to: authority.clone(),
+                thread: ctx.accounts.thread.to_account_info(),
+            },
+            &[&[
+                b"nightfury".as_ref(),
+                ctx.accounts.mint.key().as_ref(),
+                ctx.accounts.authority.key().as_ref(),
+                thread_id.as_ref(),
+                &[legacy.bump],
+            ]],
+        ))?;
+    }
+
+    // Close the config by hand, as there's no `Account` to close it through.
+    let lamports = nightfury.lamports();
+    **nightfury.try_borrow_mut_lamports()? = 0;
+    **authority.try_borrow_mut_lamports()? = authority.lamports().saturating_add(lamports);
+    nightfury.assign(&System::id());
+    nightfury.realloc(0, false)?;
+
+    Ok(())
+}
@@ -1,15 +1,15 @@
-use anchor_lang::{
-    prelude::*, solana_program::program::invoke, solana_program::sysvar::instructions,
-};
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions};
 use anchor_spl::token::{Mint, Token};
+use clockwork_sdk::state::ThreadResponse;
 use mpl_token_metadata::{
-    instruction::{builders::UpdateBuilder, InstructionBuilder, UpdateArgs},
-    state::{Data, Metadata, TokenMetadataAccount},
+    state::{Metadata, TokenMetadataAccount},
     utils::assert_owned_by,
 };
 
-use crate::state::NightFury;
-use crate::{errors::NightFuryError, state::NightFuryState};
+use crate::errors::NightFuryError;
+use crate::metaplex::UpdateMetadata;
+use crate::scheduler::{cron, CrankAccounts};
+use crate::state::{NightFury, NightFuryStatus, Overrides};
 
 #[derive(Accounts)]
 pub struct Switch<'info> {
@@ -18,11 +18,15 @@ pub struct Switch<'info> {
         seeds = [
             b"nightfury".as_ref(),
             mint.key().as_ref(),
-            nightfury.authority.key().as_ref()
+            nightfury.authority.key().as_ref(),
+            nightfury.thread_id.as_ref()
         ],
-        bump
+        bump = nightfury.bump
     )]
     pub nightfury: Box<Account<'info, NightFury>>,
+    /// CHECK: only exists once an override has been added, deserialized when it does.
+    #[account(seeds = [b"overrides".as_ref(), nightfury.key().as_ref()], bump)]
+    pub overrides: UncheckedAccount<'info>,
     // pub token: Account<'info, TokenAccount>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: make sure this is a valid metadata account and that it belongs to the mint.
@@ -33,9 +37,13 @@ pub struct Switch<'info> {
     pub master_edition: UncheckedAccount<'info>,
     /// CHECK: Make sure it's the correct delegate record.
     pub delegate_record: UncheckedAccount<'info>,
-    /// CHECK: make sure it's a valid thread
+    /// The config's Clockwork thread, or anyone cranking a permissionless config.
     #[account(mut)]
-    pub thread: Signer<'info>,
+    pub signer: Signer<'info>,
+    #[account(mut, seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
+    /// CHECK: only passed to oracle-driven modes, matched against the configured feed.
+    pub oracle: Option<UncheckedAccount<'info>>,
     /// CHECK: Make sure this account belongs to the auth rules program
     pub auth_rules: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
@@ -48,81 +56,251 @@ pub struct Switch<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn process_switch(ctx: Context<Switch>) -> Result<()> {
-    assert_owned_by(&ctx.accounts.metadata, &mpl_token_metadata::id())?;
+pub fn process_switch(ctx: Context<Switch>) -> Result<ThreadResponse> {
+    require!(
+        instructions::check_id(&ctx.accounts.instructions_sysvar.key()),
+        NightFuryError::InvalidInstructionsSysvarId
+    );
 
-    let metadata = Metadata::from_account_info(&mut ctx.accounts.metadata)?;
     let nightfury = &mut ctx.accounts.nightfury;
-    let mint = &ctx.accounts.mint;
+    if nightfury.status != NightFuryStatus::Active {
+        msg!("nightfury is {:?}, skipping switch", nightfury.status);
+        return Ok(ThreadResponse::default());
+    }
 
+    let now = Clock::get()?.unix_timestamp;
+    let crank_accounts = CrankAccounts {
+        nightfury: nightfury.key(),
+        signer: ctx.accounts.signer.to_account_info(),
+        escrow: ctx.accounts.escrow.to_account_info(),
+        escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    let backend = nightfury.scheduler.backend();
+    backend.crank(nightfury, &crank_accounts, now)?;
+    nightfury.next_switch_at = cron::next_fire(&nightfury.schedule, now).unwrap_or(i64::MAX);
+
+    let oracle = ctx
+        .accounts
+        .oracle
+        .as_ref()
+        .map(|oracle| oracle.to_account_info());
+    let nightfury_info = nightfury.to_account_info();
+    let outcome = switch_config(
+        nightfury,
+        &SwitchConfigAccounts {
+            payer: &ctx.accounts.signer.to_account_info(),
+            nightfury: &nightfury_info,
+            overrides: &ctx.accounts.overrides.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            metadata: &ctx.accounts.metadata.to_account_info(),
+            master_edition: &ctx.accounts.master_edition.to_account_info(),
+            delegate_record: &ctx.accounts.delegate_record.to_account_info(),
+            oracle: oracle.as_ref(),
+            auth_rules: &ctx.accounts.auth_rules.to_account_info(),
+            authorization_rules_program: &ctx
+                .accounts
+                .authorization_rules_program
+                .to_account_info(),
+            instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        },
+        now,
+    )?;
+
+    if outcome.updated {
+        backend.reward(nightfury, &crank_accounts)?;
+    }
+
+    // Let the thread delete itself once there's nothing left to do.
+    let mut response = ThreadResponse::default();
+    if outcome.finished {
+        response.close_to = Some(nightfury.authority);
+    }
+
+    Ok(response)
+}
+
+/// Accounts needed to bring one config's metadata up to date.
+pub struct SwitchConfigAccounts<'a, 'info> {
+    /// Pays for the metadata update.
+    pub payer: &'a AccountInfo<'info>,
+    pub nightfury: &'a AccountInfo<'info>,
+    pub overrides: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub delegate_record: &'a AccountInfo<'info>,
+    pub oracle: Option<&'a AccountInfo<'info>>,
+    pub auth_rules: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+/// What `switch_config` did to a config.
+pub struct SwitchOutcome {
+    /// The metadata had to be updated.
+    pub updated: bool,
+    /// The config just finished.
+    pub finished: bool,
+}
+
+/// Moves an active config to the phase it should show at unix time `now` and updates its
+/// metadata to match.
+pub fn switch_config(
+    nightfury: &mut NightFury,
+    accounts: &SwitchConfigAccounts,
+    now: i64,
+) -> Result<SwitchOutcome> {
+    assert_owned_by(accounts.metadata, &mpl_token_metadata::id())?;
+    let metadata = Metadata::from_account_info(accounts.metadata)?;
     require!(
-        metadata.mint == ctx.accounts.mint.key(),
-        NightFuryError::InvalidMint
-    );
-    require!(nightfury.mint == mint.key(), NightFuryError::InvalidMint);
-    require!(
-        metadata.mint.key() == mint.key(),
+        metadata.mint == accounts.mint.key(),
         NightFuryError::InvalidMint
     );
     require!(
-        mint.key() == metadata.mint.key(),
+        nightfury.mint == accounts.mint.key(),
         NightFuryError::InvalidMint
     );
-    require!(
-        instructions::check_id(&ctx.accounts.instructions_sysvar.key()),
-        NightFuryError::InvalidInstructionsSysvarId
-    );
 
-    let update_args = UpdateArgs::AsDataItemDelegateV2 {
-        data: Some(Data {
-            uri: match nightfury.state {
-                NightFuryState::Day => nightfury.night_uri.clone(),
-                NightFuryState::Night => nightfury.day_uri.clone(),
-            },
-            ..metadata.data
-        }),
-        authorization_data: None,
+    // Derive the phase from the clock so duplicate or late cranks can't drift the schedule.
+    let target_phase = nightfury.target_phase(now, accounts.oracle)?;
+    let phase = match nightfury.advance(target_phase, now) {
+        Showing::Pinned(phase) | Showing::Final(phase) => nightfury.phases[phase as usize].clone(),
+        Showing::Scheduled(phase) => {
+            // Dated overrides win over the regular schedule while they last.
+            let overrides = accounts.overrides;
+            let active_override = if overrides.data_is_empty() {
+                None
+            } else {
+                let overrides = Account::<Overrides>::try_from(overrides)?;
+                require_keys_eq!(
+                    overrides.nightfury,
+                    accounts.nightfury.key(),
+                    NightFuryError::InvalidOverrides
+                );
+                overrides.active(now).map(|entry| entry.phase.clone())
+            };
+            active_override.unwrap_or_else(|| nightfury.phases[phase as usize].clone())
+        }
     };
+    let finished = nightfury.status == NightFuryStatus::Finished;
+    if phase.is_applied(&metadata.data) {
+        msg!("metadata already up to date");
+        return Ok(SwitchOutcome {
+            updated: false,
+            finished,
+        });
+    }
 
-    let update_instruction = UpdateBuilder::new()
-        .payer(ctx.accounts.thread.key())
-        .authority(ctx.accounts.thread.key())
-        .mint(ctx.accounts.mint.key())
-        // .token(ctx.accounts.token_account.key())
-        .metadata(ctx.accounts.metadata.key())
-        .edition(ctx.accounts.master_edition.key())
-        .authorization_rules(ctx.accounts.auth_rules.key())
-        .delegate_record(ctx.accounts.delegate_record.key())
-        .authorization_rules_program(ctx.accounts.authorization_rules_program.key())
-        .build(update_args)
-        .map_err(|e| {
-            msg!("{:?}", e);
-            NightFuryError::FailedToBuildUpdateInstruction
-        })?
-        .instruction();
-
-    msg!("invoking update instruction");
-    invoke(
-        &update_instruction,
-        &[
-            ctx.accounts.thread.to_account_info(),
-            ctx.accounts.delegate_record.to_account_info(),
-            // ctx.accounts.token_account.to_account_info(),
-            ctx.accounts.mint.to_account_info(),
-            ctx.accounts.metadata.to_account_info(),
-            ctx.accounts.master_edition.to_account_info(),
-            ctx.accounts.thread.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.instructions_sysvar.to_account_info(),
-            ctx.accounts.authorization_rules_program.to_account_info(),
-            ctx.accounts.auth_rules.to_account_info(),
-        ],
-    )?;
+    UpdateMetadata {
+        payer: accounts.payer,
+        nightfury: accounts.nightfury,
+        mint: accounts.mint,
+        metadata: accounts.metadata,
+        master_edition: accounts.master_edition,
+        delegate_record: accounts.delegate_record,
+        auth_rules: accounts.auth_rules,
+        authorization_rules_program: accounts.authorization_rules_program,
+        instructions_sysvar: accounts.instructions_sysvar,
+        system_program: accounts.system_program,
+    }
+    .invoke(phase.apply(metadata.data), &nightfury.signer_seeds())?;
 
-    nightfury.state = match nightfury.state {
-        NightFuryState::Day => NightFuryState::Night,
-        NightFuryState::Night => NightFuryState::Day,
-    };
+    Ok(SwitchOutcome {
+        updated: true,
+        finished,
+    })
+}
+
+/// Which of a config's phases it should show.
+#[derive(Debug, PartialEq)]
+pub enum Showing {
+    /// Held in place by an authority pin.
+    Pinned(u8),
+    /// Finished on for good.
+    Final(u8),
+    /// The schedule's phase or the holder's pick, which dated overrides may replace.
+    Scheduled(u8),
+}
+
+impl NightFury {
+    /// Moves the config onto `target_phase` at unix time `now`, finishing it once expired or
+    /// settled, and picks what it should show.
+    pub fn advance(&mut self, target_phase: u8, now: i64) -> Showing {
+        if target_phase != self.phase {
+            self.phase = target_phase;
+            self.phase_changed_at = now;
+            self.switch_count = self.switch_count.saturating_add(1);
+            self.manual_phase = None;
+        }
+
+        // An authority pin holds the art in place, finishing included, until it runs out.
+        if let Some(pin) = self.pin.as_ref().filter(|pin| pin.is_active(now)) {
+            return Showing::Pinned(pin.phase);
+        }
+
+        // Once expired or settled, settle on the final phase for good.
+        let final_phase = match &self.expiry {
+            Some(expiry) if expiry.is_reached(now, self.switch_count) => Some(expiry.final_phase),
+            _ if self.is_settled() => Some(self.phase),
+            _ => None,
+        };
+        match final_phase {
+            Some(final_phase) => {
+                msg!("nightfury finished");
+                if self.phase != final_phase {
+                    self.phase = final_phase;
+                    self.phase_changed_at = now;
+                }
+                self.status = NightFuryStatus::Finished;
+                Showing::Final(final_phase)
+            }
+            None => Showing::Scheduled(self.manual_phase.unwrap_or(self.phase)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{test_config, Expiry};
+
+    #[test]
+    fn moves_onto_the_target_phase_once() {
+        let mut nightfury = test_config(3);
+        assert_eq!(nightfury.advance(1, 100), Showing::Scheduled(1));
+        assert_eq!(nightfury.advance(1, 200), Showing::Scheduled(1));
+        assert_eq!(nightfury.phase, 1);
+        assert_eq!(nightfury.phase_changed_at, 100);
+        assert_eq!(nightfury.switch_count, 1);
+    }
+
+    #[test]
+    fn the_holder_pick_shows_until_the_schedule_moves_on() {
+        let mut nightfury = test_config(3);
+        nightfury.manual_phase = Some(2);
+        assert_eq!(nightfury.advance(0, 100), Showing::Scheduled(2));
+        assert_eq!(nightfury.manual_phase, Some(2));
+
+        assert_eq!(nightfury.advance(1, 200), Showing::Scheduled(1));
+        assert_eq!(nightfury.manual_phase, None);
+    }
 
-    Ok(())
+    #[test]
+    fn finishes_once_expired() {
+        let mut nightfury = NightFury {
+            expiry: Some(Expiry {
+                end_at: None,
+                max_switches: Some(2),
+                final_phase: 2,
+            }),
+            ..test_config(3)
+        };
+        assert_eq!(nightfury.advance(1, 100), Showing::Scheduled(1));
+        assert_eq!(nightfury.advance(0, 200), Showing::Final(2));
+        assert_eq!(nightfury.phase, 2);
+        assert_eq!(nightfury.status, NightFuryStatus::Finished);
+    }
 }
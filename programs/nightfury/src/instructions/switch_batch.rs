@@ -0,0 +1,190 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, sysvar::instructions},
+    InstructionData,
+};
+use clockwork_sdk::state::ThreadResponse;
+use mpl_token_metadata::{
+    instruction::MetadataDelegateRole,
+    pda::{
+        find_master_edition_account, find_metadata_account, find_metadata_delegate_record_account,
+    },
+};
+
+use crate::errors::NightFuryError;
+use crate::instructions::{switch_config, SwitchConfigAccounts};
+use crate::metaplex::updatable_metadata;
+use crate::scheduler::cron;
+use crate::state::{Batch, NightFury, NightFuryStatus, Overrides, Scheduler};
+
+/// Accounts `switch_batch` takes per member in `remaining_accounts`, in this order:
+/// nightfury, overrides, mint, metadata, master edition and delegate record.
+const ACCOUNTS_PER_MEMBER: usize = 6;
+
+#[derive(Accounts)]
+pub struct SwitchBatch<'info> {
+    #[account(
+        seeds = [b"batch".as_ref(), batch.authority.as_ref(), batch.thread_id.as_ref()],
+        bump = batch.bump,
+        has_one = thread,
+        has_one = auth_rules
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+    /// The batch's thread, paying for the metadata updates.
+    #[account(mut)]
+    pub thread: Signer<'info>,
+    /// CHECK: matched against the batch.
+    pub auth_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Switches the members on `page` of the batch, then hands the thread the next page as a
+/// follow-up instruction until the whole batch is done. `None` kicks a run off and is what
+/// the thread itself is created with.
+pub fn process_switch_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwitchBatch<'info>>,
+    page: Option<u32>,
+) -> Result<ThreadResponse> {
+    let batch = &ctx.accounts.batch;
+    let next_page = match page {
+        None => 0,
+        Some(page) => {
+            let members = batch.page(page);
+            let groups = ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_MEMBER);
+            require!(
+                groups.remainder().is_empty() && groups.len() <= batch.page_size as usize,
+                NightFuryError::InvalidBatchPage
+            );
+
+            let now = Clock::get()?.unix_timestamp;
+            let next_switch_at = cron::next_fire(&batch.schedule, now).unwrap_or(i64::MAX);
+            for accounts in groups {
+                // Members can come and go while the page is queued, so they're matched by key
+                // and ones that have left the page since are skipped.
+                let nightfury = &accounts[0];
+                let member = match members
+                    .iter()
+                    .find(|member| member.nightfury == nightfury.key())
+                {
+                    Some(member) => member,
+                    None => {
+                        msg!("{} left the batch, skipping", nightfury.key());
+                        continue;
+                    }
+                };
+                require_keys_eq!(
+                    accounts[2].key(),
+                    member.mint,
+                    NightFuryError::InvalidBatchPage
+                );
+                if nightfury.data_is_empty() {
+                    msg!("{} is gone, skipping", member.nightfury);
+                    continue;
+                }
+
+                let mut config = Account::<NightFury>::try_from(nightfury)?;
+                require!(
+                    config.scheduler == Scheduler::Batch { batch: batch.key() },
+                    NightFuryError::NotInBatch
+                );
+                if config.status != NightFuryStatus::Active {
+                    continue;
+                }
+                if let Err(reason) = updatable_metadata(
+                    &batch.auth_rules,
+                    &accounts[2],
+                    &accounts[3],
+                    &accounts[4],
+                    &accounts[5],
+                ) {
+                    msg!("skipping {}: {}", member.nightfury, reason);
+                    continue;
+                }
+
+                config.next_switch_at = next_switch_at;
+                switch_config(
+                    &mut config,
+                    &SwitchConfigAccounts {
+                        payer: &ctx.accounts.thread.to_account_info(),
+                        nightfury,
+                        overrides: &accounts[1],
+                        mint: &accounts[2],
+                        metadata: &accounts[3],
+                        master_edition: &accounts[4],
+                        delegate_record: &accounts[5],
+                        oracle: None,
+                        auth_rules: &ctx.accounts.auth_rules.to_account_info(),
+                        authorization_rules_program: &ctx
+                            .accounts
+                            .authorization_rules_program
+                            .to_account_info(),
+                        instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+                        system_program: &ctx.accounts.system_program.to_account_info(),
+                    },
+                    now,
+                )?;
+                config.exit(&crate::ID)?;
+            }
+
+            page.saturating_add(1)
+        }
+    };
+
+    let mut response = ThreadResponse::default();
+    if !batch.page(next_page).is_empty() {
+        response.dynamic_instruction =
+            Some(switch_batch_instruction(batch.key(), batch, Some(next_page)).into());
+    }
+
+    Ok(response)
+}
+
+/// The `switch_batch` instruction for `page` of `batch`, with its members' accounts.
+pub fn switch_batch_instruction(
+    batch_key: Pubkey,
+    batch: &Batch,
+    page: Option<u32>,
+) -> Instruction {
+    let mut accounts = crate::accounts::SwitchBatch {
+        batch: batch_key,
+        thread: batch.thread,
+        auth_rules: batch.auth_rules,
+        token_metadata_program: mpl_token_metadata::ID,
+        instructions_sysvar: instructions::ID,
+        authorization_rules_program: mpl_token_auth_rules::ID,
+        system_program: System::id(),
+    }
+    .to_account_metas(Some(true));
+    let members = page.map_or(&[][..], |page| batch.page(page));
+    for member in members.iter().filter(|member| !member.is_vacant()) {
+        let (delegate_record, _) = find_metadata_delegate_record_account(
+            &member.mint,
+            MetadataDelegateRole::DataItem,
+            &batch.authority,
+            &member.nightfury,
+        );
+        accounts.extend([
+            AccountMeta::new(member.nightfury, false),
+            AccountMeta::new_readonly(Overrides::pubkey(member.nightfury), false),
+            AccountMeta::new_readonly(member.mint, false),
+            AccountMeta::new(find_metadata_account(&member.mint).0, false),
+            AccountMeta::new_readonly(find_master_edition_account(&member.mint).0, false),
+            AccountMeta::new_readonly(delegate_record, false),
+        ]);
+    }
+
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: crate::instruction::SwitchBatch { page }.data(),
+    }
+}
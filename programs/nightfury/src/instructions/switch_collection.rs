@@ -0,0 +1,217 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, sysvar::instructions},
+    InstructionData,
+};
+use clockwork_sdk::state::ThreadResponse;
+use mpl_token_metadata::{
+    instruction::MetadataDelegateRole,
+    pda::{
+        find_master_edition_account, find_metadata_account, find_metadata_delegate_record_account,
+    },
+};
+
+use crate::errors::NightFuryError;
+use crate::metaplex::{updatable_metadata, UpdateMetadata};
+use crate::state::{Collection, CollectionMember};
+
+/// Accounts `switch_collection` takes per member in `remaining_accounts`, in this order:
+/// member, mint, metadata, master edition and delegate record.
+const ACCOUNTS_PER_MEMBER: usize = 5;
+
+#[derive(Accounts)]
+pub struct SwitchCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"collection".as_ref(), collection.collection_mint.as_ref()],
+        bump = collection.bump,
+        has_one = thread,
+        has_one = auth_rules
+    )]
+    pub collection: Box<Account<'info, Collection>>,
+    /// The collection's thread, paying for the metadata updates.
+    #[account(mut)]
+    pub thread: Signer<'info>,
+    /// CHECK: matched against the collection.
+    pub auth_rules: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real token metadata program.
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+    /// CHECK: Manually check this against the sysvar instruction program id
+    #[account(address = instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    /// CHECK: Make sure this is the real authorization rules program.
+    #[account(address = mpl_token_auth_rules::ID)]
+    pub authorization_rules_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Switches the members on `page` of the collection, then hands the thread the next page as
+/// a follow-up instruction until every member is done. `None` kicks a run off, settling the
+/// phase the whole run shows, and is what the thread itself is created with.
+///
+/// Members are keyed by index, and their mints are only known once their accounts are read,
+/// so every page also carries the member accounts of the page after it (`page_size` of them,
+/// last in `remaining_accounts`) to build the follow-up from.
+pub fn process_switch_collection<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwitchCollection<'info>>,
+    page: Option<u32>,
+) -> Result<ThreadResponse> {
+    let collection = &mut ctx.accounts.collection;
+    let page_size = collection.page_size as usize;
+    let remaining_accounts = ctx.remaining_accounts;
+    require!(
+        remaining_accounts.len() >= page_size,
+        NightFuryError::InvalidCollectionPage
+    );
+    let (members, next_members) = remaining_accounts.split_at(remaining_accounts.len() - page_size);
+    let members = members.chunks_exact(ACCOUNTS_PER_MEMBER);
+    require!(
+        members.remainder().is_empty(),
+        NightFuryError::InvalidCollectionPage
+    );
+
+    let next_page = match page {
+        None => {
+            require!(members.len() == 0, NightFuryError::InvalidCollectionPage);
+            let now = Clock::get()?.unix_timestamp;
+            let target_phase = collection.target_phase(now)?;
+            if target_phase != collection.phase {
+                collection.phase = target_phase;
+                collection.phase_changed_at = now;
+            }
+
+            0
+        }
+        Some(page) => {
+            let indices = collection.page(page);
+            let phase = &collection.phases[collection.phase as usize];
+            for accounts in members {
+                // Members removed since this page was read ahead are gone by now.
+                if accounts[0].data_is_empty() {
+                    msg!("{} was removed, skipping", accounts[0].key());
+                    continue;
+                }
+                let member = Account::<CollectionMember>::try_from(&accounts[0])?;
+                let mint = &accounts[1];
+                require!(
+                    member.collection == collection.key()
+                        && indices.contains(&member.index)
+                        && member.mint == mint.key(),
+                    NightFuryError::InvalidCollectionPage
+                );
+
+                let metadata_account = &accounts[2];
+                let metadata = match updatable_metadata(
+                    &collection.auth_rules,
+                    mint,
+                    metadata_account,
+                    &accounts[3],
+                    &accounts[4],
+                ) {
+                    Ok(metadata) => metadata,
+                    Err(reason) => {
+                        msg!("skipping {}: {}", mint.key(), reason);
+                        continue;
+                    }
+                };
+                if phase.is_applied(&metadata.data) {
+                    continue;
+                }
+
+                UpdateMetadata {
+                    payer: &ctx.accounts.thread.to_account_info(),
+                    nightfury: &collection.to_account_info(),
+                    mint,
+                    metadata: metadata_account,
+                    master_edition: &accounts[3],
+                    delegate_record: &accounts[4],
+                    auth_rules: &ctx.accounts.auth_rules.to_account_info(),
+                    authorization_rules_program: &ctx
+                        .accounts
+                        .authorization_rules_program
+                        .to_account_info(),
+                    instructions_sysvar: &ctx.accounts.instructions_sysvar.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                }
+                .invoke(phase.apply(metadata.data), &collection.signer_seeds())?;
+            }
+
+            page.saturating_add(1)
+        }
+    };
+
+    // Read the next page's members, skipping the gaps removed members left behind.
+    let indices = collection.page(next_page);
+    if indices.is_empty() {
+        return Ok(ThreadResponse::default());
+    }
+    let mut mints = Vec::with_capacity(page_size);
+    for (index, account) in indices.zip(next_members) {
+        if account.data_is_empty() {
+            continue;
+        }
+        let member = Account::<CollectionMember>::try_from(account)?;
+        require!(
+            member.collection == collection.key() && member.index == index,
+            NightFuryError::InvalidCollectionPage
+        );
+        mints.push((account.key(), member.mint));
+    }
+
+    Ok(ThreadResponse {
+        dynamic_instruction: Some(
+            switch_collection_instruction(collection.key(), collection, Some(next_page), &mints)
+                .into(),
+        ),
+        ..ThreadResponse::default()
+    })
+}
+
+/// The `switch_collection` instruction for `page` of `collection`, switching `members` (each
+/// a member account and its mint) and reading ahead to the page after.
+pub fn switch_collection_instruction(
+    collection_key: Pubkey,
+    collection: &Collection,
+    page: Option<u32>,
+    members: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = crate::accounts::SwitchCollection {
+        collection: collection_key,
+        thread: collection.thread,
+        auth_rules: collection.auth_rules,
+        token_metadata_program: mpl_token_metadata::ID,
+        instructions_sysvar: instructions::ID,
+        authorization_rules_program: mpl_token_auth_rules::ID,
+        system_program: System::id(),
+    }
+    .to_account_metas(Some(true));
+    for (member, mint) in members {
+        let (delegate_record, _) = find_metadata_delegate_record_account(
+            mint,
+            MetadataDelegateRole::DataItem,
+            &collection.authority,
+            &collection_key,
+        );
+        accounts.extend([
+            AccountMeta::new_readonly(*member, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(find_metadata_account(mint).0, false),
+            AccountMeta::new_readonly(find_master_edition_account(mint).0, false),
+            AccountMeta::new_readonly(delegate_record, false),
+        ]);
+    }
+    let next_page = page.map_or(0, |page| page.saturating_add(1));
+    let start = next_page.saturating_mul(collection.page_size.into());
+    accounts.extend(
+        (start..start.saturating_add(collection.page_size.into())).map(|index| {
+            AccountMeta::new_readonly(CollectionMember::pubkey(collection_key, index), false)
+        }),
+    );
+
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: crate::instruction::SwitchCollection { page }.data(),
+    }
+}
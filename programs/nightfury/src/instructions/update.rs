@@ -1,11 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Mint;
-use clockwork_sdk::{
-    cpi::{thread_update, ThreadUpdate},
-    state::{Thread, ThreadAccount, Trigger},
-    ThreadProgram,
-};
+use clockwork_sdk::ThreadProgram;
 
+use crate::errors::NightFuryError;
+use crate::scheduler::{cron, SchedulerAccounts};
 use crate::state::NightFury;
 
 #[derive(Accounts)]
@@ -14,40 +12,36 @@ pub struct Update<'info> {
     pub mint: Box<Account<'info, Mint>>,
     #[account(mut, has_one = mint, has_one = thread, has_one = authority)]
     pub nightfury: Account<'info, NightFury>,
-    #[account(mut, address = thread.pubkey(), constraint = thread.authority.eq(&nightfury.key()))]
-    pub thread: Account<'info, Thread>,
+    /// CHECK: matched against the config, only exists for Clockwork configs.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(seeds = [b"escrow".as_ref(), nightfury.key().as_ref()], bump)]
+    pub escrow: SystemAccount<'info>,
     #[account(address = clockwork_sdk::ID)]
     pub thread_program: Program<'info, ThreadProgram>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn process_update(ctx: Context<Update>, schedule: String) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    cron::validate(&schedule, now)?;
+
+    let nightfury = &mut ctx.accounts.nightfury;
+    nightfury.next_switch_at =
+        cron::next_fire(&schedule, now).ok_or(NightFuryError::ScheduleExhausted)?;
+    nightfury.schedule = schedule;
+
     let nightfury = &ctx.accounts.nightfury;
-    thread_update(
-        CpiContext::new_with_signer(
-            ctx.accounts.thread_program.to_account_info(),
-            ThreadUpdate {
-                authority: ctx.accounts.nightfury.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                thread: ctx.accounts.thread.to_account_info(),
-            },
-            &[&[
-                b"nightfury".as_ref(),
-                ctx.accounts.mint.key().as_ref(),
-                ctx.accounts.authority.key().as_ref(),
-                ctx.accounts.nightfury.thread_id.as_ref(),
-                &[nightfury.bump],
-            ]],
-        ),
-        clockwork_sdk::state::ThreadSettings {
-            fee: None,
-            instructions: None,
-            name: None,
-            rate_limit: None,
-            trigger: Some(Trigger::Cron {
-                schedule,
-                skippable: true,
-            }),
+    nightfury.scheduler.backend().reschedule(
+        nightfury,
+        &SchedulerAccounts {
+            nightfury: nightfury.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            thread: ctx.accounts.thread.to_account_info(),
+            thread_program: ctx.accounts.thread_program.to_account_info(),
+            escrow: ctx.accounts.escrow.to_account_info(),
+            escrow_bump: *ctx.bumps.get("escrow").unwrap(),
+            system_program: ctx.accounts.system_program.to_account_info(),
         },
     )?;
 
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::state::{Collection, NightFury, Phase};
+
+#[derive(Accounts)]
+pub struct UpdateCollectionPhase<'info> {
+    #[account(mut, has_one = authority)]
+    pub collection: Box<Account<'info, Collection>>,
+    pub authority: Signer<'info>,
+}
+
+/// Replaces a phase for every member at once; they pick it up on the thread's next run.
+pub fn process_update_collection_phase(
+    ctx: Context<UpdateCollectionPhase>,
+    index: u8,
+    phase: Phase,
+) -> Result<()> {
+    let collection = &mut ctx.accounts.collection;
+
+    let mut phases = collection.phases.clone();
+    let slot = phases
+        .get_mut(index as usize)
+        .ok_or(NightFuryError::InvalidPhaseIndex)?;
+    *slot = phase;
+    NightFury::validate_phases(&phases)?;
+    collection.phases = phases;
+
+    Ok(())
+}
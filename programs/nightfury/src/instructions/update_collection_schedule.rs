@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use clockwork_sdk::{
+    cpi::{thread_update, ThreadUpdate},
+    state::{ThreadSettings, Trigger},
+    ThreadProgram,
+};
+
+use crate::scheduler::cron;
+use crate::state::Collection;
+
+#[derive(Accounts)]
+pub struct UpdateCollectionSchedule<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = authority, has_one = thread)]
+    pub collection: Box<Account<'info, Collection>>,
+    /// CHECK: matched against the collection.
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+    #[account(address = clockwork_sdk::ID)]
+    pub thread_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves the whole collection onto a new cron schedule.
+pub fn process_update_collection_schedule(
+    ctx: Context<UpdateCollectionSchedule>,
+    schedule: String,
+) -> Result<()> {
+    cron::validate(&schedule, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.collection.schedule = schedule;
+
+    let collection = &ctx.accounts.collection;
+    thread_update(
+        CpiContext::new_with_signer(
+            ctx.accounts.thread_program.to_account_info(),
+            ThreadUpdate {
+                authority: collection.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                thread: ctx.accounts.thread.to_account_info(),
+            },
+            &[&collection.signer_seeds()],
+        ),
+        ThreadSettings {
+            fee: None,
+            instructions: None,
+            name: None,
+            rate_limit: None,
+            trigger: Some(Trigger::Cron {
+                schedule: collection.schedule.clone(),
+                skippable: true,
+            }),
+        },
+    )
+}
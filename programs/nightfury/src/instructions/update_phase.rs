@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::state::{NightFury, Phase};
+
+#[derive(Accounts)]
+pub struct UpdatePhase<'info> {
+    #[account(mut, has_one = authority)]
+    pub nightfury: Account<'info, NightFury>,
+    pub authority: Signer<'info>,
+}
+
+pub fn process_update_phase(ctx: Context<UpdatePhase>, index: u8, phase: Phase) -> Result<()> {
+    let nightfury = &mut ctx.accounts.nightfury;
+
+    let mut phases = nightfury.phases.clone();
+    let slot = phases
+        .get_mut(index as usize)
+        .ok_or(NightFuryError::InvalidPhaseIndex)?;
+
+    // set phase
+    *slot = phase;
+    NightFury::validate_phases(&phases)?;
+    nightfury.phases = phases;
+
+    Ok(())
+}
@@ -1,10 +1,17 @@
 mod errors;
 mod instructions;
+mod metaplex;
+mod oracle;
+mod phase;
+mod scheduler;
 mod state;
+mod system;
 
 use anchor_lang::prelude::*;
+use clockwork_sdk::state::ThreadResponse;
 
 use instructions::*;
+use state::{Phase, ThreadConfig};
 
 declare_id!("3L6ghU2yzZe8BuBw1assJsL2ckX9HxF2TY4iTjugycQi");
 
@@ -15,29 +22,165 @@ pub mod nightfury {
     pub fn initialize(
         ctx: Context<Initialize>,
         thread_id: Vec<u8>,
-        day_uri: String,
-        night_uri: String,
+        args: InitializeArgs,
     ) -> Result<()> {
-        process_initialize(ctx, thread_id, day_uri, night_uri)
+        process_initialize(ctx, thread_id, args)
     }
 
-    pub fn switch(ctx: Context<Switch>) -> Result<()> {
+    pub fn switch(ctx: Context<Switch>) -> Result<ThreadResponse> {
         process_switch(ctx)
     }
 
-    pub fn update_day_uri(ctx: Context<UpdateDayUri>, uri: String) -> Result<()> {
-        process_update_day_uri(ctx, uri)
+    pub fn holder_switch(ctx: Context<HolderSwitch>, phase: Option<u8>) -> Result<()> {
+        process_holder_switch(ctx, phase)
     }
 
-    pub fn update_night_uri(ctx: Context<UpdateNightUri>, uri: String) -> Result<()> {
-        process_update_night_uri(ctx, uri)
+    pub fn update_phase(ctx: Context<UpdatePhase>, index: u8, phase: Phase) -> Result<()> {
+        process_update_phase(ctx, index, phase)
+    }
+
+    pub fn add_override(
+        ctx: Context<AddOverride>,
+        start_at: i64,
+        end_at: i64,
+        phase: Phase,
+    ) -> Result<()> {
+        process_add_override(ctx, start_at, end_at, phase)
+    }
+
+    pub fn remove_override(ctx: Context<RemoveOverride>, index: u8) -> Result<()> {
+        process_remove_override(ctx, index)
+    }
+
+    pub fn pin_phase(ctx: Context<PinPhase>, phase: u8, until: i64) -> Result<()> {
+        process_pin_phase(ctx, phase, until)
+    }
+
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        process_pause(ctx)
+    }
+
+    pub fn resume(ctx: Context<Resume>) -> Result<()> {
+        process_resume(ctx)
     }
 
     pub fn revoke(ctx: Context<Revoke>) -> Result<()> {
         process_revoke(ctx)
     }
 
+    pub fn revoke_legacy(ctx: Context<RevokeLegacy>, thread_id: Vec<u8>) -> Result<()> {
+        process_revoke_legacy(ctx, thread_id)
+    }
+
     pub fn update(ctx: Context<Update>, schedule: String) -> Result<()> {
         process_update(ctx, schedule)
     }
+
+    pub fn configure_thread(
+        ctx: Context<ConfigureThread>,
+        thread_config: ThreadConfig,
+    ) -> Result<()> {
+        process_configure_thread(ctx, thread_config)
+    }
+
+    pub fn fund_thread(ctx: Context<FundThread>, amount: u64) -> Result<()> {
+        process_fund_thread(ctx, amount)
+    }
+
+    pub fn withdraw_thread(ctx: Context<WithdrawThread>, amount: u64) -> Result<()> {
+        process_withdraw_thread(ctx, amount)
+    }
+
+    pub fn migrate_thread(
+        ctx: Context<MigrateThread>,
+        thread_id: Vec<u8>,
+        funding: u64,
+    ) -> Result<()> {
+        process_migrate_thread(ctx, thread_id, funding)
+    }
+
+    pub fn create_batch(
+        ctx: Context<CreateBatch>,
+        thread_id: Vec<u8>,
+        schedule: String,
+        page_size: u8,
+        funding: u64,
+    ) -> Result<()> {
+        process_create_batch(ctx, thread_id, schedule, page_size, funding)
+    }
+
+    pub fn add_to_batch(ctx: Context<AddToBatch>) -> Result<()> {
+        process_add_to_batch(ctx)
+    }
+
+    pub fn remove_from_batch(ctx: Context<RemoveFromBatch>, nightfury: Pubkey) -> Result<()> {
+        process_remove_from_batch(ctx, nightfury)
+    }
+
+    pub fn switch_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwitchBatch<'info>>,
+        page: Option<u32>,
+    ) -> Result<ThreadResponse> {
+        process_switch_batch(ctx, page)
+    }
+
+    pub fn close_batch(ctx: Context<CloseBatch>) -> Result<()> {
+        process_close_batch(ctx)
+    }
+
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        thread_id: Vec<u8>,
+        args: CreateCollectionArgs,
+    ) -> Result<()> {
+        process_create_collection(ctx, thread_id, args)
+    }
+
+    pub fn register_member(ctx: Context<RegisterMember>) -> Result<()> {
+        process_register_member(ctx)
+    }
+
+    pub fn batch_register<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchRegister<'info>>,
+    ) -> Result<()> {
+        process_batch_register(ctx)
+    }
+
+    pub fn update_collection_phase(
+        ctx: Context<UpdateCollectionPhase>,
+        index: u8,
+        phase: Phase,
+    ) -> Result<()> {
+        process_update_collection_phase(ctx, index, phase)
+    }
+
+    pub fn update_collection_schedule(
+        ctx: Context<UpdateCollectionSchedule>,
+        schedule: String,
+    ) -> Result<()> {
+        process_update_collection_schedule(ctx, schedule)
+    }
+
+    pub fn pause_collection(ctx: Context<PauseCollection>) -> Result<()> {
+        process_pause_collection(ctx)
+    }
+
+    pub fn resume_collection(ctx: Context<ResumeCollection>) -> Result<()> {
+        process_resume_collection(ctx)
+    }
+
+    pub fn switch_collection<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwitchCollection<'info>>,
+        page: Option<u32>,
+    ) -> Result<ThreadResponse> {
+        process_switch_collection(ctx, page)
+    }
+
+    pub fn remove_member(ctx: Context<RemoveMember>) -> Result<()> {
+        process_remove_member(ctx)
+    }
+
+    pub fn close_collection(ctx: Context<CloseCollection>) -> Result<()> {
+        process_close_collection(ctx)
+    }
 }
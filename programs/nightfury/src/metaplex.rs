@@ -0,0 +1,234 @@
+//! Metadata updates signed by the NightFury or collection PDA holding the data item
+//! delegate, and the authority-signed calls that hand the delegate out and take it back.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::{invoke, invoke_signed},
+};
+use mpl_token_metadata::{
+    instruction::{
+        builders::{DelegateBuilder, RevokeBuilder, UpdateBuilder},
+        DelegateArgs, InstructionBuilder, RevokeArgs, UpdateArgs,
+    },
+    state::{Data, Metadata, ProgrammableConfig, TokenMetadataAccount},
+};
+
+use crate::errors::NightFuryError;
+
+/// The authorization rule set a programmable NFT's updates are checked against, if any.
+pub fn rule_set(metadata: &Metadata) -> Option<Pubkey> {
+    match metadata.programmable_config {
+        Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+        None => None,
+    }
+}
+
+/// A mint's metadata if the delegate can update it right now, otherwise why not. Paged runs
+/// skip mints that fail this, as one failed update would take the rest of the run with it.
+pub fn updatable_metadata(
+    auth_rules: &Pubkey,
+    mint: &AccountInfo,
+    metadata: &AccountInfo,
+    master_edition: &AccountInfo,
+    delegate_record: &AccountInfo,
+) -> std::result::Result<Metadata, &'static str> {
+    let is_live = |account: &AccountInfo| {
+        *account.owner == mpl_token_metadata::ID && !account.data_is_empty()
+    };
+    if !is_live(metadata) || !is_live(master_edition) {
+        return Err("token was burned");
+    }
+    if !is_live(delegate_record) {
+        return Err("delegate was revoked");
+    }
+    let metadata = match Metadata::from_account_info(metadata) {
+        Ok(metadata) if metadata.mint == mint.key() => metadata,
+        _ => return Err("metadata doesn't match the mint"),
+    };
+    if matches!(rule_set(&metadata), Some(rule_set) if rule_set != *auth_rules) {
+        return Err("metadata uses other auth rules");
+    }
+
+    Ok(metadata)
+}
+
+pub struct UpdateMetadata<'a, 'info> {
+    pub payer: &'a AccountInfo<'info>,
+    /// The NightFury or collection PDA acting as delegate.
+    pub nightfury: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub delegate_record: &'a AccountInfo<'info>,
+    pub auth_rules: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UpdateMetadata<'a, 'info> {
+    /// Replaces the metadata `data`, signing as the delegate with `nightfury_seeds`.
+    pub fn invoke(&self, data: Data, nightfury_seeds: &[&[u8]]) -> Result<()> {
+        let update_args = UpdateArgs::AsDataItemDelegateV2 {
+            data: Some(data),
+            authorization_data: None,
+        };
+
+        let update_instruction = UpdateBuilder::new()
+            .payer(self.payer.key())
+            .authority(self.nightfury.key())
+            .mint(self.mint.key())
+            .metadata(self.metadata.key())
+            .edition(self.master_edition.key())
+            .authorization_rules(self.auth_rules.key())
+            .delegate_record(self.delegate_record.key())
+            .authorization_rules_program(self.authorization_rules_program.key())
+            .build(update_args)
+            .map_err(|e| {
+                msg!("{:?}", e);
+                NightFuryError::FailedToBuildUpdateInstruction
+            })?
+            .instruction();
+
+        msg!("invoking update instruction");
+        invoke_signed(
+            &update_instruction,
+            &[
+                self.nightfury.clone(),
+                self.delegate_record.clone(),
+                self.mint.clone(),
+                self.metadata.clone(),
+                self.master_edition.clone(),
+                self.payer.clone(),
+                self.system_program.clone(),
+                self.instructions_sysvar.clone(),
+                self.authorization_rules_program.clone(),
+                self.auth_rules.clone(),
+            ],
+            &[nightfury_seeds],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Makes a NightFury or collection PDA the data item delegate of a mint, approved by its
+/// update authority.
+pub struct DelegateMetadata<'a, 'info> {
+    /// Update authority of the metadata, also paying for the delegate record.
+    pub authority: &'a AccountInfo<'info>,
+    pub nightfury: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_account: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub delegate_record: &'a AccountInfo<'info>,
+    pub auth_rules: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> DelegateMetadata<'a, 'info> {
+    pub fn invoke(&self) -> Result<()> {
+        let delegate_args = DelegateArgs::DataItemV1 {
+            authorization_data: None,
+        };
+        let delegate_instruction = DelegateBuilder::new()
+            .delegate(self.nightfury.key())
+            .metadata(self.metadata.key())
+            .master_edition(self.master_edition.key())
+            .authority(self.authority.key())
+            .payer(self.authority.key())
+            .mint(self.mint.key())
+            .token(self.token_account.key())
+            .spl_token_program(self.token_program.key())
+            .delegate_record(self.delegate_record.key())
+            .authorization_rules(self.auth_rules.key())
+            .authorization_rules_program(mpl_token_auth_rules::ID)
+            .system_program(self.system_program.key())
+            .build(delegate_args)
+            .unwrap()
+            .instruction();
+
+        invoke(
+            &delegate_instruction,
+            &[
+                self.delegate_record.clone(),
+                self.nightfury.clone(),
+                self.metadata.clone(),
+                self.master_edition.clone(),
+                self.mint.clone(),
+                self.token_account.clone(),
+                self.authority.clone(),
+                self.authority.clone(),
+                self.system_program.clone(),
+                self.instructions_sysvar.clone(),
+                self.token_program.clone(),
+                self.authorization_rules_program.clone(),
+                self.auth_rules.clone(),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Takes the data item delegate back from a NightFury or collection PDA, signed by the update
+/// authority.
+pub struct RevokeDelegate<'a, 'info> {
+    pub authority: &'a AccountInfo<'info>,
+    pub nightfury: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub delegate_record: &'a AccountInfo<'info>,
+    pub auth_rules: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> RevokeDelegate<'a, 'info> {
+    pub fn invoke(&self) -> Result<()> {
+        let revoke_args = RevokeArgs::DataItemV1 {};
+        let revoke_instruction = RevokeBuilder::new()
+            .delegate_record(self.delegate_record.key())
+            .delegate(self.nightfury.key())
+            .metadata(self.metadata.key())
+            .master_edition(self.master_edition.key())
+            .mint(self.mint.key())
+            .authority(self.authority.key())
+            .payer(self.authority.key())
+            .system_program(self.system_program.key())
+            .sysvar_instructions(self.instructions_sysvar.key())
+            .spl_token_program(self.token_program.key())
+            .authorization_rules_program(self.authorization_rules_program.key())
+            .authorization_rules(self.auth_rules.key())
+            .build(revoke_args)
+            .unwrap()
+            .instruction();
+
+        invoke(
+            &revoke_instruction,
+            &[
+                self.delegate_record.clone(),
+                self.nightfury.clone(),
+                self.metadata.clone(),
+                self.master_edition.clone(),
+                self.mint.clone(),
+                self.authority.clone(),
+                self.authority.clone(),
+                self.system_program.clone(),
+                self.instructions_sysvar.clone(),
+                self.token_program.clone(),
+                self.authorization_rules_program.clone(),
+                self.auth_rules.clone(),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! Generic data feeds for arbitrary signals such as weather.
+//!
+//! Any program can publish a feed by owning an account with this little-endian layout:
+//!
+//! | offset | type  | field                                    |
+//! |--------|-------|------------------------------------------|
+//! | 0      | `u32` | magic, `0x4e46_4446`                     |
+//! | 4      | `u64` | value, a measurement or an enum index    |
+//! | 12     | `i64` | unix time the value was last written at  |
+
+use anchor_lang::prelude::*;
+
+use super::read;
+use crate::errors::NightFuryError;
+
+pub const MAGIC: u32 = 0x4e46_4446;
+const VALUE_OFFSET: usize = 4;
+const TIMESTAMP_OFFSET: usize = 12;
+const LENGTH: usize = 20;
+
+/// Value of a data feed, rejected if it's more than `max_age` seconds old at unix time `now`.
+pub fn value(account: &AccountInfo, max_age: u32, now: i64) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= LENGTH && u32::from_le_bytes(read(&data, 0)) == MAGIC,
+        NightFuryError::InvalidDataFeed
+    );
+    let timestamp = i64::from_le_bytes(read(&data, TIMESTAMP_OFFSET));
+    require!(
+        now.saturating_sub(timestamp) <= max_age as i64,
+        NightFuryError::StaleDataFeed
+    );
+
+    Ok(u64::from_le_bytes(read(&data, VALUE_OFFSET)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::with_account;
+    use crate::phase::PhaseContext;
+    use crate::state::{DstRule, FeedRule, PhaseMode, Timezone};
+
+    /// 2023-06-21T00:00:00Z.
+    const NOW: i64 = 1_687_305_600;
+    const MAX_AGE: u32 = 3_600;
+
+    fn feed(value: u64, timestamp: i64) -> Vec<u8> {
+        let mut data = vec![0; LENGTH];
+        data[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[VALUE_OFFSET..VALUE_OFFSET + 8].copy_from_slice(&value.to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&timestamp.to_le_bytes());
+        data
+    }
+
+    fn read_value(data: Vec<u8>) -> Result<u64> {
+        with_account(Pubkey::new_unique(), data, |account| {
+            value(account, MAX_AGE, NOW)
+        })
+    }
+
+    fn rule(min_value: u64, phase: u8) -> FeedRule {
+        FeedRule { min_value, phase }
+    }
+
+    #[test]
+    fn reads_fresh_values() {
+        assert_eq!(read_value(feed(2, NOW)).unwrap(), 2);
+        assert_eq!(read_value(feed(u64::MAX, NOW - 60)).unwrap(), u64::MAX);
+        // Clocks drift, so a feed from slightly ahead is still fresh.
+        assert_eq!(read_value(feed(3, NOW + 5)).unwrap(), 3);
+    }
+
+    #[test]
+    fn rejects_stale_values() {
+        assert_eq!(read_value(feed(2, NOW - MAX_AGE as i64)).unwrap(), 2);
+        for timestamp in [NOW - MAX_AGE as i64 - 1, 0, i64::MIN] {
+            assert_eq!(
+                read_value(feed(2, timestamp)).unwrap_err(),
+                NightFuryError::StaleDataFeed.into()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        // A Pyth account's magic, the likeliest wrong account to be passed in.
+        let mut wrong_magic = feed(2, NOW);
+        wrong_magic[..4].copy_from_slice(&0xa1b2_c3d4_u32.to_le_bytes());
+        let mut short = feed(2, NOW);
+        short.truncate(LENGTH - 1);
+        for data in [wrong_magic, short, Vec::new()] {
+            assert_eq!(
+                read_value(data).unwrap_err(),
+                NightFuryError::InvalidDataFeed.into()
+            );
+        }
+    }
+
+    #[test]
+    fn phase_comes_from_the_last_rule_reached() {
+        let key = Pubkey::new_unique();
+        // Weather codes: 0-9 clear, 10-19 rainy, 20 and up stormy.
+        let mode = PhaseMode::DataFeed {
+            feed: key,
+            max_age: MAX_AGE,
+            rules: vec![rule(0, 0), rule(10, 1), rule(20, 2)],
+        };
+        let timezone = Timezone {
+            utc_offset: 0,
+            dst: DstRule::None,
+        };
+        let context = PhaseContext {
+            phase_count: 3,
+            phase: 0,
+            phase_changed_at: 0,
+            timezone: &timezone,
+        };
+        let phase = |value| {
+            with_account(key, feed(value, NOW), |account| {
+                mode.target_phase(&context, NOW, Some(account))
+            })
+        };
+        assert_eq!(phase(0).unwrap(), 0);
+        assert_eq!(phase(9).unwrap(), 0);
+        assert_eq!(phase(10).unwrap(), 1);
+        assert_eq!(phase(19).unwrap(), 1);
+        assert_eq!(phase(20).unwrap(), 2);
+        assert_eq!(phase(u64::MAX).unwrap(), 2);
+    }
+
+    #[test]
+    fn validates_the_rules() {
+        let mode = |max_age, rules| PhaseMode::DataFeed {
+            feed: Pubkey::new_unique(),
+            max_age,
+            rules,
+        };
+        assert!(mode(MAX_AGE, vec![rule(0, 1), rule(5, 0)])
+            .validate(2)
+            .is_ok());
+        assert_eq!(
+            mode(0, vec![rule(0, 0)]).validate(2).unwrap_err(),
+            NightFuryError::InvalidMaxAge.into()
+        );
+        for rules in [
+            Vec::new(),
+            // Values below the first rule would have no phase.
+            vec![rule(1, 0)],
+            vec![rule(0, 0), rule(5, 1), rule(5, 0)],
+            vec![rule(0, 0), rule(5, 2)],
+            (0..=PhaseMode::MAX_FEED_RULES as u64)
+                .map(|min_value| rule(min_value, 0))
+                .collect(),
+        ] {
+            assert_eq!(
+                mode(MAX_AGE, rules).validate(2).unwrap_err(),
+                NightFuryError::InvalidFeedRules.into()
+            );
+        }
+    }
+}
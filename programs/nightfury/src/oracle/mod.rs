@@ -0,0 +1,33 @@
+//! Readers for the oracle accounts that feed-driven phase modes consume.
+
+pub mod data_feed;
+pub mod pyth;
+
+/// `N` bytes of `data` starting at `offset`; callers check the account length up front.
+fn read<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&data[offset..offset + N]);
+    bytes
+}
+
+/// Runs `f` on an account keyed `key` holding `data`, as the oracle readers would see it.
+#[cfg(test)]
+fn with_account<T>(
+    key: anchor_lang::prelude::Pubkey,
+    mut data: Vec<u8>,
+    f: impl FnOnce(&anchor_lang::prelude::AccountInfo) -> T,
+) -> T {
+    let owner = anchor_lang::prelude::Pubkey::default();
+    let mut lamports = 0;
+    let account = anchor_lang::prelude::AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    f(&account)
+}
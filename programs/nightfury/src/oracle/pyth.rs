@@ -0,0 +1,193 @@
+//! Pyth v2 price accounts, read by offset so no Pyth crate is needed on-chain.
+
+use anchor_lang::prelude::*;
+
+use super::read;
+use crate::errors::NightFuryError;
+
+const MAGIC: u32 = 0xa1b2_c3d4;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+/// Unix time the aggregate price was published at.
+const TIMESTAMP_OFFSET: usize = 96;
+const PRICE_OFFSET: usize = 208;
+const STATUS_OFFSET: usize = 224;
+/// Everything up to and including the aggregate price's publish slot.
+const MIN_LENGTH: usize = 240;
+
+/// Aggregate price of a Pyth price account, in the feed's fixed-point units. Rejected if it
+/// was published more than `max_age` seconds before unix time `now`, as a feed that stopped
+/// updating keeps reporting its last price as trading.
+pub fn price(account: &AccountInfo, max_age: u32, now: i64) -> Result<i64> {
+    let data = account.try_borrow_data()?;
+    require!(data.len() >= MIN_LENGTH, NightFuryError::InvalidPriceFeed);
+    require!(
+        u32::from_le_bytes(read(&data, 0)) == MAGIC
+            && u32::from_le_bytes(read(&data, ACCOUNT_TYPE_OFFSET)) == ACCOUNT_TYPE_PRICE,
+        NightFuryError::InvalidPriceFeed
+    );
+    require!(
+        u32::from_le_bytes(read(&data, STATUS_OFFSET)) == STATUS_TRADING,
+        NightFuryError::PriceUnavailable
+    );
+    let timestamp = i64::from_le_bytes(read(&data, TIMESTAMP_OFFSET));
+    require!(
+        now.saturating_sub(timestamp) <= max_age as i64,
+        NightFuryError::StalePriceFeed
+    );
+
+    Ok(i64::from_le_bytes(read(&data, PRICE_OFFSET)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::with_account;
+    use crate::phase::PhaseContext;
+    use crate::state::{DstRule, PhaseMode, Timezone};
+
+    /// 2023-06-21T00:00:00Z.
+    const NOW: i64 = 1_687_305_600;
+    const MAX_AGE: u32 = 60;
+
+    /// A trading Pyth price account quoting `price`, published at `NOW`.
+    fn price_account(price: i64) -> Vec<u8> {
+        published_at(price, NOW)
+    }
+
+    fn published_at(price: i64, timestamp: i64) -> Vec<u8> {
+        let mut data = vec![0; MIN_LENGTH];
+        data[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4]
+            .copy_from_slice(&ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&timestamp.to_le_bytes());
+        data[PRICE_OFFSET..PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[STATUS_OFFSET..STATUS_OFFSET + 4].copy_from_slice(&STATUS_TRADING.to_le_bytes());
+        data
+    }
+
+    fn read_price(data: Vec<u8>) -> Result<i64> {
+        with_account(Pubkey::new_unique(), data, |account| {
+            price(account, MAX_AGE, NOW)
+        })
+    }
+
+    #[test]
+    fn reads_the_aggregate_price() {
+        assert_eq!(
+            read_price(price_account(2_150_000_000)).unwrap(),
+            2_150_000_000
+        );
+        assert_eq!(read_price(price_account(-42)).unwrap(), -42);
+    }
+
+    #[test]
+    fn reads_longer_accounts() {
+        // Real price accounts carry their publishers' quotes after the aggregate.
+        let mut data = price_account(7);
+        data.resize(3_312, 0xff);
+        assert_eq!(read_price(data).unwrap(), 7);
+    }
+
+    #[test]
+    fn rejects_short_accounts() {
+        let mut data = price_account(7);
+        data.truncate(MIN_LENGTH - 1);
+        assert_eq!(
+            read_price(data).unwrap_err(),
+            NightFuryError::InvalidPriceFeed.into()
+        );
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let mut wrong_magic = price_account(7);
+        wrong_magic[0] ^= 1;
+        // Product accounts share the magic but have account type 2.
+        let mut product = price_account(7);
+        product[ACCOUNT_TYPE_OFFSET] = 2;
+        for data in [wrong_magic, product] {
+            assert_eq!(
+                read_price(data).unwrap_err(),
+                NightFuryError::InvalidPriceFeed.into()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_prices_that_are_not_trading() {
+        let mut data = price_account(7);
+        // Halted.
+        data[STATUS_OFFSET] = 2;
+        assert_eq!(
+            read_price(data).unwrap_err(),
+            NightFuryError::PriceUnavailable.into()
+        );
+    }
+
+    #[test]
+    fn rejects_stale_prices() {
+        assert_eq!(
+            read_price(published_at(7, NOW - MAX_AGE as i64)).unwrap(),
+            7
+        );
+        // A feed that stopped updating still reports its last price as trading.
+        for timestamp in [NOW - MAX_AGE as i64 - 1, 0] {
+            assert_eq!(
+                read_price(published_at(7, timestamp)).unwrap_err(),
+                NightFuryError::StalePriceFeed.into()
+            );
+        }
+    }
+
+    #[test]
+    fn price_feed_phase_counts_the_thresholds_reached() {
+        let feed = Pubkey::new_unique();
+        let mode = PhaseMode::PriceFeed {
+            feed,
+            max_age: MAX_AGE,
+            thresholds: vec![100, 200],
+        };
+        let timezone = Timezone {
+            utc_offset: 0,
+            dst: DstRule::None,
+        };
+        let context = PhaseContext {
+            phase_count: 3,
+            phase: 0,
+            phase_changed_at: 0,
+            timezone: &timezone,
+        };
+        let phase = |price| {
+            with_account(feed, price_account(price), |account| {
+                mode.target_phase(&context, NOW, Some(account))
+            })
+        };
+        assert_eq!(phase(99).unwrap(), 0);
+        assert_eq!(phase(100).unwrap(), 1);
+        assert_eq!(phase(199).unwrap(), 1);
+        assert_eq!(phase(200).unwrap(), 2);
+
+        // Only the configured feed is read.
+        let other = with_account(Pubkey::new_unique(), price_account(150), |account| {
+            mode.target_phase(&context, NOW, Some(account))
+        });
+        assert_eq!(other.unwrap_err(), NightFuryError::InvalidOracle.into());
+    }
+
+    #[test]
+    fn validates_the_max_age() {
+        let mode = |max_age| PhaseMode::PriceFeed {
+            feed: Pubkey::new_unique(),
+            max_age,
+            thresholds: vec![100],
+        };
+        assert!(mode(MAX_AGE).validate(2).is_ok());
+        assert_eq!(
+            mode(0).validate(2).unwrap_err(),
+            NightFuryError::InvalidMaxAge.into()
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Proleptic Gregorian calendar helpers over days since the unix epoch.
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days since 1970-01-01 for a civil date (Howard Hinnant's `days_from_civil`).
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Civil `(year, month, day)` for a count of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Civil date of a unix timestamp.
+pub fn civil_from_timestamp(timestamp: i64) -> (i64, u32, u32) {
+    civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY))
+}
+
+/// Day of the week for a count of days since 1970-01-01, with Sunday as 0.
+pub fn weekday(days: i64) -> i64 {
+    (days + 4).rem_euclid(7)
+}
+
+/// Days since 1970-01-01 of the `nth` (1-based) Sunday of a month.
+pub fn nth_sunday(year: i64, month: u32, nth: i64) -> i64 {
+    let first = days_from_civil(year, month, 1);
+    first + (7 - weekday(first)) % 7 + (nth - 1) * 7
+}
+
+/// Days since 1970-01-01 of the last Sunday of a month.
+pub fn last_sunday(year: i64, month: u32) -> i64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let last = days_from_civil(next_year, next_month, 1) - 1;
+    last - weekday(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_from_civil_matches_known_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(2023, 6, 21), 19_529);
+        assert_eq!(days_from_civil(1600, 1, 1), -135_140);
+    }
+
+    #[test]
+    fn leap_years() {
+        assert_eq!(
+            civil_from_days(days_from_civil(2024, 2, 29) + 1),
+            (2024, 3, 1)
+        );
+        assert_eq!(
+            civil_from_days(days_from_civil(2000, 2, 28) + 1),
+            (2000, 2, 29)
+        );
+        assert_eq!(
+            civil_from_days(days_from_civil(1900, 2, 28) + 1),
+            (1900, 3, 1)
+        );
+        assert_eq!(
+            civil_from_days(days_from_civil(2023, 12, 31) + 1),
+            (2024, 1, 1)
+        );
+    }
+
+    #[test]
+    fn civil_from_days_round_trips() {
+        for days in (-800_000..800_000).step_by(97) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn civil_from_timestamp_floors_to_the_day() {
+        assert_eq!(civil_from_timestamp(1_687_305_600), (2023, 6, 21));
+        assert_eq!(civil_from_timestamp(1_687_305_599), (2023, 6, 20));
+        assert_eq!(civil_from_timestamp(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn weekdays_and_sundays() {
+        // 1970-01-01 was a Thursday.
+        assert_eq!(weekday(0), 4);
+        assert_eq!(weekday(-1), 3);
+        assert_eq!(nth_sunday(2023, 3, 2), days_from_civil(2023, 3, 12));
+        assert_eq!(nth_sunday(2023, 10, 1), days_from_civil(2023, 10, 1));
+        assert_eq!(last_sunday(2023, 10), days_from_civil(2023, 10, 29));
+        assert_eq!(last_sunday(2023, 12), days_from_civil(2023, 12, 31));
+    }
+}
@@ -0,0 +1,69 @@
+/// Mean synodic month (29.530588853 days) in milliseconds.
+const SYNODIC_MONTH_MS: i64 = 2_551_442_877;
+/// Reference new moon: 2000-01-06 18:14 UTC.
+const NEW_MOON: i64 = 947_182_440;
+
+pub const MOON_PHASES: usize = 8;
+
+/// Moon phase at unix time `now`, from 0 (new moon) through 7 (waning crescent).
+///
+/// Uses the mean lunation, so a phase can start up to about half a day off the true moon,
+/// which is fine for art that a daily crank refreshes.
+pub fn moon_phase(now: i64) -> u8 {
+    let age = now
+        .saturating_sub(NEW_MOON)
+        .saturating_mul(1_000)
+        .rem_euclid(SYNODIC_MONTH_MS);
+
+    // Each phase is centred on its namesake, so the new moon spans the last and first
+    // sixteenth of the cycle.
+    let phase = (age * 2 * MOON_PHASES as i64 + SYNODIC_MONTH_MS) / (2 * SYNODIC_MONTH_MS);
+    (phase % MOON_PHASES as i64) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_new_moons() {
+        for new_moon in [
+            934_369_680,   // 1999-08-11 11:08 UTC, before the reference new moon
+            1_674_334_380, // 2023-01-21 20:53 UTC
+            1_712_600_460, // 2024-04-08 18:21 UTC
+        ] {
+            assert_eq!(moon_phase(new_moon), 0);
+        }
+    }
+
+    #[test]
+    fn published_full_moons() {
+        for full_moon in [
+            1_667_905_320, // 2022-11-08 11:02 UTC
+            1_673_046_480, // 2023-01-06 23:08 UTC
+            1_693_445_700, // 2023-08-31 01:35 UTC
+            1_726_626_840, // 2024-09-18 02:34 UTC
+        ] {
+            assert_eq!(moon_phase(full_moon), 4);
+        }
+    }
+
+    #[test]
+    fn published_quarters() {
+        // 2023-01-28 15:19 and 2023-01-15 02:10 UTC.
+        assert_eq!(moon_phase(1_674_919_140), 2);
+        assert_eq!(moon_phase(1_673_748_600), 6);
+    }
+
+    #[test]
+    fn steps_through_every_phase_in_order() {
+        let mut phases = vec![moon_phase(NEW_MOON)];
+        for hour in 1..=(SYNODIC_MONTH_MS / 3_600_000) {
+            let phase = moon_phase(NEW_MOON + hour * 3_600);
+            if phase != *phases.last().unwrap() {
+                phases.push(phase);
+            }
+        }
+        assert_eq!(phases, [0, 1, 2, 3, 4, 5, 6, 7, 0]);
+    }
+}
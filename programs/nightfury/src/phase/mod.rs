@@ -0,0 +1,315 @@
+mod calendar;
+mod lunar;
+mod season;
+mod solar;
+mod timezone;
+
+use anchor_lang::prelude::*;
+
+use crate::errors::NightFuryError;
+use crate::oracle::{data_feed, pyth};
+use crate::state::{Collection, NightFury, PhaseMode, Timezone};
+
+use solar::Daylight;
+
+impl PhaseMode {
+    pub fn validate(&self, phase_count: usize) -> Result<()> {
+        match self {
+            PhaseMode::Cycle { period, .. } => {
+                require!(*period > 0, NightFuryError::InvalidPeriod);
+            }
+            PhaseMode::Solar {
+                latitude,
+                longitude,
+                altitude,
+            } => {
+                require!(
+                    phase_count == 2 || phase_count == 4,
+                    NightFuryError::InvalidPhaseCount
+                );
+                require!(
+                    (-90_000_000..=90_000_000).contains(latitude)
+                        && (-180_000_000..=180_000_000).contains(longitude),
+                    NightFuryError::InvalidCoordinates
+                );
+                require!(
+                    !matches!(altitude, Some(altitude) if *altitude > solar::MAX_ALTITUDE),
+                    NightFuryError::InvalidAltitude
+                );
+            }
+            PhaseMode::Lunar => {
+                require!(
+                    phase_count == lunar::MOON_PHASES,
+                    NightFuryError::InvalidPhaseCount
+                );
+            }
+            PhaseMode::Seasonal { .. } => {
+                require!(
+                    phase_count == season::SEASONS,
+                    NightFuryError::InvalidPhaseCount
+                );
+            }
+            PhaseMode::PriceFeed {
+                max_age,
+                thresholds,
+                ..
+            } => {
+                require!(*max_age > 0, NightFuryError::InvalidMaxAge);
+                require!(
+                    thresholds.len() + 1 == phase_count
+                        && thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+                    NightFuryError::InvalidThresholds
+                );
+            }
+            PhaseMode::DataFeed { max_age, rules, .. } => {
+                require!(*max_age > 0, NightFuryError::InvalidMaxAge);
+                require!(
+                    (1..=Self::MAX_FEED_RULES).contains(&rules.len())
+                        && rules[0].min_value == 0
+                        && rules
+                            .windows(2)
+                            .all(|pair| pair[0].min_value < pair[1].min_value)
+                        && rules.iter().all(|rule| (rule.phase as usize) < phase_count),
+                    NightFuryError::InvalidFeedRules
+                );
+            }
+            PhaseMode::Evolution { stage_duration } => {
+                require!(*stage_duration > 0, NightFuryError::InvalidPeriod);
+            }
+            PhaseMode::Reveal { .. } => {
+                require!(phase_count == 2, NightFuryError::InvalidPhaseCount);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What picking a phase needs to know about the config or collection being switched.
+pub struct PhaseContext<'a> {
+    pub phase_count: usize,
+    /// Phase showing right now, which one-way modes move on from.
+    pub phase: u8,
+    pub phase_changed_at: i64,
+    pub timezone: &'a Timezone,
+}
+
+impl PhaseMode {
+    /// Index of the phase that should be showing at unix time `now`, reading `oracle` for
+    /// feed-driven modes.
+    pub fn target_phase(
+        &self,
+        context: &PhaseContext,
+        now: i64,
+        oracle: Option<&AccountInfo>,
+    ) -> Result<u8> {
+        let phase_count = context.phase_count;
+        let timezone = context.timezone;
+        let phase = match self {
+            PhaseMode::Cycle { period, offset } => {
+                let local = timezone.local_time(now);
+                let elapsed = local.saturating_sub(*offset).div_euclid(*period);
+                elapsed.rem_euclid(phase_count as i64) as u8
+            }
+            PhaseMode::Solar {
+                latitude,
+                longitude,
+                altitude,
+            } => match (
+                solar::daylight(*latitude, *longitude, *altitude, now),
+                phase_count,
+            ) {
+                (Daylight::Day, 2) => 0,
+                (_, 2) => 1,
+                (Daylight::Dawn, _) => 0,
+                (Daylight::Day, _) => 1,
+                (Daylight::Dusk, _) => 2,
+                (Daylight::Night, _) => 3,
+            },
+            PhaseMode::Lunar => lunar::moon_phase(now),
+            PhaseMode::Seasonal { hemisphere } => {
+                season::season(timezone.local_time(now), *hemisphere)
+            }
+            PhaseMode::PriceFeed {
+                feed,
+                max_age,
+                thresholds,
+            } => {
+                let oracle = oracle
+                    .filter(|oracle| oracle.key() == *feed)
+                    .ok_or(NightFuryError::InvalidOracle)?;
+                let price = pyth::price(oracle, *max_age, now)?;
+                thresholds
+                    .iter()
+                    .filter(|&&threshold| price >= threshold)
+                    .count() as u8
+            }
+            PhaseMode::DataFeed {
+                feed,
+                max_age,
+                rules,
+            } => {
+                let oracle = oracle
+                    .filter(|oracle| oracle.key() == *feed)
+                    .ok_or(NightFuryError::InvalidOracle)?;
+                let value = data_feed::value(oracle, *max_age, now)?;
+                rules
+                    .iter()
+                    .rev()
+                    .find(|rule| value >= rule.min_value)
+                    .map_or(0, |rule| rule.phase)
+            }
+            // Advance one way only, a stage at a time, and stay on the last one.
+            PhaseMode::Evolution { stage_duration } => {
+                let last_stage = phase_count as i64 - 1;
+                let stages = now.saturating_sub(context.phase_changed_at).max(0) / stage_duration;
+                (context.phase as i64)
+                    .saturating_add(stages)
+                    .min(last_stage) as u8
+            }
+            PhaseMode::Reveal { reveal_at } => u8::from(now >= *reveal_at),
+        };
+
+        Ok(phase)
+    }
+}
+
+impl NightFury {
+    pub fn target_phase(&self, now: i64, oracle: Option<&AccountInfo>) -> Result<u8> {
+        self.mode.target_phase(
+            &PhaseContext {
+                phase_count: self.phases.len(),
+                phase: self.phase,
+                phase_changed_at: self.phase_changed_at,
+                timezone: &self.timezone,
+            },
+            now,
+            oracle,
+        )
+    }
+
+    /// Whether a one-way mode has reached its last phase and has nothing left to do.
+    pub fn is_settled(&self) -> bool {
+        self.mode.is_one_way() && self.phase as usize == self.phases.len() - 1
+    }
+}
+
+impl Collection {
+    /// Index of the phase every member should be showing at unix time `now`.
+    pub fn target_phase(&self, now: i64) -> Result<u8> {
+        self.mode.target_phase(
+            &PhaseContext {
+                phase_count: self.phases.len(),
+                phase: self.phase,
+                phase_changed_at: self.phase_changed_at,
+                timezone: &self.timezone,
+            },
+            now,
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DstRule;
+
+    const UTC: Timezone = Timezone {
+        utc_offset: 0,
+        dst: DstRule::None,
+    };
+
+    fn target(mode: &PhaseMode, phase_count: usize, phase: u8, changed_at: i64, now: i64) -> u8 {
+        mode.target_phase(
+            &PhaseContext {
+                phase_count,
+                phase,
+                phase_changed_at: changed_at,
+                timezone: &UTC,
+            },
+            now,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn cycles_wrap_around() {
+        let mode = PhaseMode::Cycle {
+            period: 3600,
+            offset: 0,
+        };
+        let phases: Vec<u8> = (0..7)
+            .map(|hour| target(&mode, 3, 0, 0, hour * 3600))
+            .collect();
+        assert_eq!(phases, [0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(target(&mode, 3, 0, 0, 3599), 0);
+        // Times before the offset wrap backwards rather than underflowing.
+        assert_eq!(target(&mode, 3, 0, 0, -1), 2);
+    }
+
+    #[test]
+    fn cycles_only_depend_on_the_clock() {
+        let mode = PhaseMode::Cycle {
+            period: 3600,
+            offset: 1800,
+        };
+        let now = 1_700_000_000 + 1800;
+        let expected = target(&mode, 4, 0, 0, now);
+        for phase in 0..4 {
+            assert_eq!(target(&mode, 4, phase, now - 5, now), expected);
+        }
+    }
+
+    #[test]
+    fn cycles_follow_the_timezone() {
+        let mode = PhaseMode::Cycle {
+            period: 3600,
+            offset: 0,
+        };
+        let context = PhaseContext {
+            phase_count: 2,
+            phase: 0,
+            phase_changed_at: 0,
+            timezone: &Timezone {
+                utc_offset: 3600,
+                dst: DstRule::None,
+            },
+        };
+        assert_eq!(mode.target_phase(&context, 0, None).unwrap(), 1);
+    }
+
+    #[test]
+    fn evolution_moves_a_stage_at_a_time_and_stops_on_the_last() {
+        let mode = PhaseMode::Evolution {
+            stage_duration: 100,
+        };
+        assert_eq!(target(&mode, 3, 0, 1_000, 1_099), 0);
+        assert_eq!(target(&mode, 3, 0, 1_000, 1_100), 1);
+        assert_eq!(target(&mode, 3, 1, 1_100, 1_199), 1);
+        assert_eq!(target(&mode, 3, 1, 1_100, 1_200), 2);
+        assert_eq!(target(&mode, 3, 2, 1_000, 10_000), 2);
+    }
+
+    #[test]
+    fn evolution_never_goes_back() {
+        let mode = PhaseMode::Evolution {
+            stage_duration: 100,
+        };
+        // A stage counted from the change time alone would be 0 here, and a clock behind the
+        // last change would be negative; either way the current stage holds.
+        assert_eq!(target(&mode, 4, 2, 1_000, 1_050), 2);
+        assert_eq!(target(&mode, 4, 2, 1_000, 500), 2);
+        assert_eq!(target(&mode, 4, 3, 1_000, 1_000), 3);
+    }
+
+    #[test]
+    fn reveal_shows_the_placeholder_until_reveal_at() {
+        let mode = PhaseMode::Reveal { reveal_at: 5_000 };
+        assert_eq!(target(&mode, 2, 0, 0, 0), 0);
+        assert_eq!(target(&mode, 2, 0, 0, 4_999), 0);
+        assert_eq!(target(&mode, 2, 0, 0, 5_000), 1);
+        assert_eq!(target(&mode, 2, 1, 5_000, 1_000_000), 1);
+    }
+}
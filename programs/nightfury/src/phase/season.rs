@@ -0,0 +1,63 @@
+use super::calendar::civil_from_timestamp;
+use crate::state::Hemisphere;
+
+pub const SEASONS: usize = 4;
+
+/// Meteorological season for a local timestamp, from 0 (spring) through 3 (winter).
+pub fn season(local: i64, hemisphere: Hemisphere) -> u8 {
+    let (_, month, _) = civil_from_timestamp(local);
+    // March, April and May are northern spring.
+    let northern = ((month + 9) % 12 / 3) as u8;
+    match hemisphere {
+        Hemisphere::Northern => northern,
+        Hemisphere::Southern => (northern + 2) % SEASONS as u8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phase::calendar::{days_from_civil, SECONDS_PER_DAY};
+
+    const SPRING: u8 = 0;
+    const SUMMER: u8 = 1;
+    const AUTUMN: u8 = 2;
+    const WINTER: u8 = 3;
+
+    fn midnight(year: i64, month: u32, day: u32) -> i64 {
+        days_from_civil(year, month, day) * SECONDS_PER_DAY
+    }
+
+    /// The season `before` ends the last second of the month before `month`, `after` starts
+    /// the first second of it.
+    fn assert_boundary(hemisphere: Hemisphere, month: u32, before: u8, after: u8) {
+        let start = midnight(2023, month, 1);
+        assert_eq!(season(start - 1, hemisphere), before);
+        assert_eq!(season(start, hemisphere), after);
+    }
+
+    #[test]
+    fn northern_boundaries() {
+        assert_boundary(Hemisphere::Northern, 3, WINTER, SPRING);
+        assert_boundary(Hemisphere::Northern, 6, SPRING, SUMMER);
+        assert_boundary(Hemisphere::Northern, 9, SUMMER, AUTUMN);
+        assert_boundary(Hemisphere::Northern, 12, AUTUMN, WINTER);
+    }
+
+    #[test]
+    fn southern_boundaries() {
+        assert_boundary(Hemisphere::Southern, 3, SUMMER, AUTUMN);
+        assert_boundary(Hemisphere::Southern, 6, AUTUMN, WINTER);
+        assert_boundary(Hemisphere::Southern, 9, WINTER, SPRING);
+        assert_boundary(Hemisphere::Southern, 12, SPRING, SUMMER);
+    }
+
+    #[test]
+    fn seasons_span_the_new_year() {
+        let new_year = midnight(2024, 1, 1);
+        for now in [new_year - 1, new_year, midnight(2024, 2, 29)] {
+            assert_eq!(season(now, Hemisphere::Northern), WINTER);
+            assert_eq!(season(now, Hemisphere::Southern), SUMMER);
+        }
+    }
+}
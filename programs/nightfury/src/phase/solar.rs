@@ -0,0 +1,340 @@
+//! Fixed-point sunrise/sunset calculation.
+//!
+//! Implements the "sunrise equation" (see the NOAA / Wikipedia formulation) with every
+//! quantity held as an integer scaled by [`SCALE`]: angles in nano-degrees, times in
+//! nano-days since J2000 and trigonometric results in billionths. Accuracy is well within
+//! a minute for latitudes outside the polar circles, which is plenty for switching art.
+
+/// Fixed-point scale shared by every value in this module.
+const SCALE: i64 = 1_000_000_000;
+const PI: i64 = 3_141_592_654;
+const DEGREES_90: i64 = 90 * SCALE;
+const DEGREES_180: i64 = 180 * SCALE;
+const DEGREES_360: i64 = 360 * SCALE;
+/// Seconds between the unix epoch and J2000 (2000-01-01 12:00 UTC).
+const J2000_UNIX: i64 = 946_728_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Sun altitude at sunrise/sunset: refraction plus the solar disc radius (-0.833°).
+const SUNRISE_ALTITUDE: i64 = -833_000_000;
+/// Sun altitude at the start of civil dawn and end of civil dusk (-6°).
+const CIVIL_TWILIGHT_ALTITUDE: i64 = -6 * SCALE;
+/// Obliquity of the ecliptic (23.4397°).
+const OBLIQUITY: i64 = 23_439_700_000;
+/// Highest observer altitude accepted, in meters: above any summit an observer could be
+/// standing on, and low enough to keep the horizon dip a few degrees.
+pub const MAX_ALTITUDE: u32 = 10_000;
+
+/// Sun position relative to an observer at a given moment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Daylight {
+    Night,
+    Dawn,
+    Day,
+    Dusk,
+}
+
+/// Classifies unix time `now` for an observer at `latitude`/`longitude` (micro-degrees,
+/// east positive) standing `altitude` meters above the horizon they're looking at.
+pub fn daylight(latitude: i32, longitude: i32, altitude: Option<u32>, now: i64) -> Daylight {
+    let latitude = latitude as i64 * 1_000;
+    let longitude = longitude as i64 * 1_000;
+    let days = div(now as i128 - J2000_UNIX as i128, SECONDS_PER_DAY as i128);
+
+    // Mean solar noon closest to `now`, then corrected to the true transit.
+    let noon = (days + longitude / 360 + SCALE / 2).div_euclid(SCALE) * SCALE - longitude / 360;
+    let anomaly = (357_529_100_000 + mul(noon, 985_600_280)).rem_euclid(DEGREES_360);
+    let center = mul(1_914_800_000, sin(anomaly))
+        + mul(20_000_000, sin(2 * anomaly))
+        + mul(300_000, sin(3 * anomaly));
+    let ecliptic_longitude =
+        (anomaly + center + DEGREES_180 + 102_937_200_000).rem_euclid(DEGREES_360);
+    let transit = noon + mul(5_300_000, sin(anomaly)) - mul(6_900_000, sin(2 * ecliptic_longitude));
+
+    let sin_declination = mul(sin(ecliptic_longitude), sin(OBLIQUITY));
+    let cos_declination = sqrt(SCALE * SCALE - sin_declination * sin_declination);
+
+    // Observers above the surrounding terrain see the sun earlier and for longer.
+    let dip = altitude.map_or(0, |altitude| {
+        mul(2_076_000_000, sqrt(altitude as i64 * 1_000_000) * 1_000_000) / 60
+    });
+    let half_day = |sun_altitude: i64| {
+        let numerator = sin(sun_altitude - dip) - mul(sin(latitude), sin_declination);
+        let denominator = mul(cos(latitude), cos_declination);
+        let cos_hour_angle = if denominator == 0 {
+            if numerator > 0 {
+                SCALE
+            } else {
+                -SCALE
+            }
+        } else {
+            div(numerator as i128, denominator as i128).clamp(-SCALE, SCALE)
+        };
+        acos(cos_hour_angle) / 360
+    };
+
+    // Half days of 0.5 mean the sun never drops below that altitude (polar summer).
+    let sun = half_day(SUNRISE_ALTITUDE);
+    let twilight = half_day(CIVIL_TWILIGHT_ALTITUDE);
+    let from_transit = days - transit;
+    if from_transit.abs() < sun || sun >= SCALE / 2 {
+        Daylight::Day
+    } else if from_transit.abs() < twilight || twilight >= SCALE / 2 {
+        if from_transit < 0 {
+            Daylight::Dawn
+        } else {
+            Daylight::Dusk
+        }
+    } else {
+        Daylight::Night
+    }
+}
+
+fn mul(a: i64, b: i64) -> i64 {
+    (a as i128 * b as i128 / SCALE as i128) as i64
+}
+
+fn div(a: i128, b: i128) -> i64 {
+    (a * SCALE as i128 / b) as i64
+}
+
+fn sqrt(value: i64) -> i64 {
+    if value <= 0 {
+        return 0;
+    }
+    // Newton's method on integers, starting above the root.
+    let mut x = value;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Sine of an angle in nano-degrees.
+fn sin(degrees: i64) -> i64 {
+    let mut degrees = degrees.rem_euclid(DEGREES_360);
+    let mut sign = 1;
+    if degrees >= DEGREES_180 {
+        degrees -= DEGREES_180;
+        sign = -1;
+    }
+    if degrees > DEGREES_90 {
+        degrees = DEGREES_180 - degrees;
+    }
+
+    // Taylor series around zero, accurate to ~1e-9 on [0, π/2].
+    let radians = (degrees as i128 * PI as i128 / DEGREES_180 as i128) as i64;
+    let squared = mul(radians, radians);
+    let mut term = radians;
+    let mut sum = radians;
+    for k in 1..=6 {
+        term = -mul(term, squared) / ((2 * k) * (2 * k + 1));
+        sum += term;
+    }
+    sign * sum
+}
+
+fn cos(degrees: i64) -> i64 {
+    sin(degrees + DEGREES_90)
+}
+
+/// Arc cosine in nano-degrees, found by bisection since cosine is monotonic on [0°, 180°].
+fn acos(value: i64) -> i64 {
+    if value <= -SCALE {
+        return DEGREES_180;
+    }
+    let (mut low, mut high) = (0, DEGREES_180);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if cos(mid) > value {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::NightFuryError;
+    use crate::state::PhaseMode;
+
+    /// 2023-06-21T00:00:00Z.
+    const JUNE_21: i64 = 1_687_305_600;
+    /// 2023-12-21T00:00:00Z.
+    const DECEMBER_21: i64 = 1_703_116_800;
+    const HOUR: i64 = 3_600;
+    const MINUTE: i64 = 60;
+    /// Published times are rounded to the minute, so allow a little either side.
+    const TOLERANCE: i64 = 2 * MINUTE;
+
+    /// Every change of daylight in the 24 hours from `start`, sampled each minute.
+    fn changes(
+        latitude: i32,
+        longitude: i32,
+        altitude: Option<u32>,
+        start: i64,
+    ) -> Vec<(i64, Daylight)> {
+        let mut previous = daylight(latitude, longitude, altitude, start);
+        let mut changes = Vec::new();
+        for now in (start..start + 24 * HOUR).step_by(MINUTE as usize) {
+            let current = daylight(latitude, longitude, altitude, now);
+            if current != previous {
+                changes.push((now, current));
+                previous = current;
+            }
+        }
+        changes
+    }
+
+    /// When the sun rises and sets in the 24 hours from `start`.
+    fn sunrise_and_sunset(latitude: i32, longitude: i32, start: i64) -> (i64, i64) {
+        let changes = changes(latitude, longitude, None, start);
+        let find = |from: Daylight, to: Daylight| {
+            changes
+                .windows(2)
+                .find(|pair| pair[0].1 == from && pair[1].1 == to)
+                .map(|pair| pair[1].0)
+                .unwrap()
+        };
+        (
+            find(Daylight::Dawn, Daylight::Day),
+            find(Daylight::Day, Daylight::Dusk),
+        )
+    }
+
+    fn assert_near(actual: i64, expected: i64) {
+        assert!(
+            (actual - expected).abs() <= TOLERANCE,
+            "{} is {} seconds off {}",
+            actual,
+            actual - expected,
+            expected
+        );
+    }
+
+    #[test]
+    fn london_summer_solstice() {
+        // 04:43 and 21:21 BST.
+        let (sunrise, sunset) = sunrise_and_sunset(51_507_400, -127_800, JUNE_21);
+        assert_near(sunrise, JUNE_21 + 3 * HOUR + 43 * MINUTE);
+        assert_near(sunset, JUNE_21 + 20 * HOUR + 21 * MINUTE);
+    }
+
+    #[test]
+    fn new_york_winter_solstice() {
+        // 07:16 and 16:32 EST.
+        let (sunrise, sunset) = sunrise_and_sunset(40_712_800, -74_006_000, DECEMBER_21);
+        assert_near(sunrise, DECEMBER_21 + 12 * HOUR + 16 * MINUTE);
+        assert_near(sunset, DECEMBER_21 + 21 * HOUR + 32 * MINUTE);
+    }
+
+    #[test]
+    fn quito_equinox() {
+        // 06:18 and 18:24 ECT on 2023-03-20, twelve hours apart on the equator.
+        let march_20 = JUNE_21 - 93 * 24 * HOUR;
+        let (sunrise, sunset) = sunrise_and_sunset(-180_700, -78_467_800, march_20);
+        assert_near(sunrise, march_20 + 11 * HOUR + 18 * MINUTE);
+        assert_near(sunset, march_20 + 23 * HOUR + 24 * MINUTE);
+    }
+
+    #[test]
+    fn sydney_wraps_the_utc_date() {
+        // 05:41 and 20:05 AEDT on the 21st: sunrise is still the 20th in UTC.
+        let start = DECEMBER_21 - 12 * HOUR;
+        let (sunrise, sunset) = sunrise_and_sunset(-33_868_800, 151_209_300, start);
+        assert_near(sunrise, DECEMBER_21 - 5 * HOUR - 19 * MINUTE);
+        assert_near(sunset, DECEMBER_21 + 9 * HOUR + 5 * MINUTE);
+        assert_eq!(
+            daylight(-33_868_800, 151_209_300, None, DECEMBER_21),
+            Daylight::Day
+        );
+    }
+
+    #[test]
+    fn honolulu_wraps_the_utc_date() {
+        // 05:50 and 19:16 HST on the 21st: sunset is already the 22nd in UTC.
+        let start = JUNE_21 + 12 * HOUR;
+        let (sunrise, sunset) = sunrise_and_sunset(21_306_900, -157_858_300, start);
+        assert_near(sunrise, JUNE_21 + 15 * HOUR + 50 * MINUTE);
+        assert_near(sunset, JUNE_21 + 29 * HOUR + 16 * MINUTE);
+        assert_eq!(
+            daylight(21_306_900, -157_858_300, None, JUNE_21 + 24 * HOUR),
+            Daylight::Day
+        );
+    }
+
+    #[test]
+    fn polar_day() {
+        // Tromsø under the midnight sun, and McMurdo Station in the southern summer.
+        for (latitude, longitude, start) in [
+            (69_649_200, 18_955_300, JUNE_21),
+            (-77_846_300, 166_668_200, DECEMBER_21),
+        ] {
+            assert_eq!(daylight(latitude, longitude, None, start), Daylight::Day);
+            assert!(changes(latitude, longitude, None, start).is_empty());
+        }
+    }
+
+    #[test]
+    fn polar_night() {
+        // Longyearbyen and McMurdo Station never see twilight at midwinter.
+        for (latitude, longitude, start) in [
+            (78_223_200, 15_626_700, DECEMBER_21),
+            (-77_846_300, 166_668_200, JUNE_21),
+        ] {
+            assert_eq!(daylight(latitude, longitude, None, start), Daylight::Night);
+            assert!(changes(latitude, longitude, None, start).is_empty());
+        }
+    }
+
+    #[test]
+    fn polar_night_keeps_its_twilight() {
+        // Tromsø's sun stays below the horizon at midwinter, but only just around noon.
+        let changes = changes(69_649_200, 18_955_300, None, DECEMBER_21);
+        assert!(!changes.is_empty());
+        assert!(changes
+            .iter()
+            .all(|(_, daylight)| *daylight != Daylight::Day));
+        assert_eq!(
+            daylight(69_649_200, 18_955_300, None, DECEMBER_21 + 11 * HOUR),
+            Daylight::Dusk
+        );
+    }
+
+    #[test]
+    fn altitude_lengthens_the_day() {
+        let (sunrise, sunset) = sunrise_and_sunset(51_507_400, -127_800, JUNE_21);
+        let changes = changes(51_507_400, -127_800, Some(1_000), JUNE_21);
+        let high_sunrise = changes
+            .iter()
+            .find(|(_, daylight)| *daylight == Daylight::Day)
+            .unwrap()
+            .0;
+        let high_sunset = changes
+            .iter()
+            .find(|(_, daylight)| *daylight == Daylight::Dusk)
+            .unwrap()
+            .0;
+        assert!(high_sunrise < sunrise - 5 * MINUTE);
+        assert!(high_sunset > sunset + 5 * MINUTE);
+    }
+
+    #[test]
+    fn validates_the_altitude() {
+        let solar = |altitude| PhaseMode::Solar {
+            latitude: 51_507_400,
+            longitude: -127_800,
+            altitude,
+        };
+        assert!(solar(None).validate(2).is_ok());
+        assert!(solar(Some(MAX_ALTITUDE)).validate(4).is_ok());
+        assert_eq!(
+            solar(Some(MAX_ALTITUDE + 1)).validate(2).unwrap_err(),
+            NightFuryError::InvalidAltitude.into()
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+
+use super::calendar::{civil_from_timestamp, last_sunday, nth_sunday, SECONDS_PER_DAY};
+use crate::errors::NightFuryError;
+use crate::state::{DstRule, Timezone};
+
+const HOUR: i64 = 60 * 60;
+const MAX_UTC_OFFSET: i32 = 14 * HOUR as i32;
+
+impl Timezone {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (-MAX_UTC_OFFSET..=MAX_UTC_OFFSET).contains(&self.utc_offset),
+            NightFuryError::InvalidUtcOffset
+        );
+
+        Ok(())
+    }
+
+    /// Wall-clock time at unix time `now`, expressed as seconds since the local epoch.
+    pub fn local_time(&self, now: i64) -> i64 {
+        let standard = now + self.utc_offset as i64;
+        if self.dst.is_active(now, standard) {
+            standard + HOUR
+        } else {
+            standard
+        }
+    }
+}
+
+impl DstRule {
+    /// Whether daylight saving applies at unix time `now`, given the zone's standard time.
+    fn is_active(&self, now: i64, standard: i64) -> bool {
+        let (year, _, _) = civil_from_timestamp(standard);
+        let at = |days: i64, hour: i64| days * SECONDS_PER_DAY + hour * HOUR;
+        match self {
+            DstRule::None => false,
+            // Second Sunday in March to the first Sunday in November, 02:00 local.
+            DstRule::NorthAmerica => {
+                (at(nth_sunday(year, 3, 2), 2)..at(nth_sunday(year, 11, 1), 1)).contains(&standard)
+            }
+            // Last Sunday in March to the last Sunday in October, 01:00 UTC.
+            DstRule::Europe => {
+                (at(last_sunday(year, 3), 1)..at(last_sunday(year, 10), 1)).contains(&now)
+            }
+            // First Sunday in October to the first Sunday in April, 02:00 local standard.
+            DstRule::Australia => {
+                standard >= at(nth_sunday(year, 10, 1), 2)
+                    || standard < at(nth_sunday(year, 4, 1), 2)
+            }
+            // Last Sunday in September to the first Sunday in April, 02:00 local standard.
+            DstRule::NewZealand => {
+                standard >= at(last_sunday(year, 9), 2) || standard < at(nth_sunday(year, 4, 1), 2)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(hours: i32, dst: DstRule) -> Timezone {
+        Timezone {
+            utc_offset: hours * HOUR as i32,
+            dst,
+        }
+    }
+
+    /// Daylight saving starts at unix time `instant`, jumping the wall clock an hour ahead.
+    fn assert_springs_forward(zone: &Timezone, instant: i64) {
+        let offset = zone.utc_offset as i64;
+        assert_eq!(zone.local_time(instant - 1), instant - 1 + offset);
+        assert_eq!(zone.local_time(instant), instant + offset + HOUR);
+    }
+
+    /// Daylight saving ends at unix time `instant`, repeating the wall clock's last hour.
+    fn assert_falls_back(zone: &Timezone, instant: i64) {
+        let offset = zone.utc_offset as i64;
+        assert_eq!(zone.local_time(instant - 1), instant - 1 + offset + HOUR);
+        assert_eq!(zone.local_time(instant), instant + offset);
+    }
+
+    #[test]
+    fn validates_the_offset() {
+        assert!(zone(14, DstRule::None).validate().is_ok());
+        assert!(zone(-14, DstRule::None).validate().is_ok());
+        assert_eq!(
+            zone(15, DstRule::None).validate().unwrap_err(),
+            NightFuryError::InvalidUtcOffset.into()
+        );
+    }
+
+    #[test]
+    fn no_dst() {
+        let tokyo = zone(9, DstRule::None);
+        for now in [1_678_604_400, 1_687_305_600, 1_704_027_600] {
+            assert_eq!(tokyo.local_time(now), now + 9 * HOUR);
+        }
+    }
+
+    #[test]
+    fn north_america() {
+        let new_york = zone(-5, DstRule::NorthAmerica);
+        // 2023-03-12 02:00 EST and 2023-11-05 02:00 EDT.
+        assert_springs_forward(&new_york, 1_678_604_400);
+        assert_falls_back(&new_york, 1_699_164_000);
+    }
+
+    #[test]
+    fn europe() {
+        // 2023-03-26 and 2023-10-29 at 01:00 UTC, whatever the zone.
+        for hours in [0, 1, 2] {
+            let zone = zone(hours, DstRule::Europe);
+            assert_springs_forward(&zone, 1_679_792_400);
+            assert_falls_back(&zone, 1_698_541_200);
+        }
+    }
+
+    #[test]
+    fn australia() {
+        let sydney = zone(10, DstRule::Australia);
+        // 2023-04-02 03:00 AEDT and 2023-10-01 02:00 AEST.
+        assert_falls_back(&sydney, 1_680_364_800);
+        assert_springs_forward(&sydney, 1_696_089_600);
+    }
+
+    #[test]
+    fn new_zealand() {
+        let auckland = zone(12, DstRule::NewZealand);
+        // 2023-04-02 03:00 NZDT and 2023-09-24 02:00 NZST.
+        assert_falls_back(&auckland, 1_680_357_600);
+        assert_springs_forward(&auckland, 1_695_477_600);
+    }
+
+    #[test]
+    fn southern_summer_spans_the_new_year() {
+        // 2023-12-31 13:00 UTC, midnight on New Year's Day in Sydney.
+        let new_year = 1_704_027_600;
+        for zone in [zone(10, DstRule::Australia), zone(12, DstRule::NewZealand)] {
+            let offset = zone.utc_offset as i64 + HOUR;
+            for now in [new_year - 2 * HOUR, new_year, new_year + 2 * HOUR] {
+                assert_eq!(zone.local_time(now), now + offset);
+            }
+        }
+    }
+}
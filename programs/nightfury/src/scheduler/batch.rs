@@ -0,0 +1,61 @@
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+
+use super::{CrankAccounts, SchedulerAccounts, SchedulerBackend};
+use crate::errors::NightFuryError;
+use crate::state::NightFury;
+
+/// Switched by the thread of a shared batch through `switch_batch`, on the batch's schedule.
+/// The config holds no thread or balance of its own; joining and leaving the batch are
+/// instructions on the batch.
+pub struct Batched;
+
+impl<'info> SchedulerBackend<'info> for Batched {
+    fn start(
+        &self,
+        _config: &NightFury,
+        _accounts: &SchedulerAccounts<'info>,
+        _switch_instruction: Instruction,
+        funding: u64,
+    ) -> Result<()> {
+        // The batch's thread pays for everything.
+        require!(funding == 0, NightFuryError::UnsupportedScheduler);
+
+        Ok(())
+    }
+
+    fn reschedule(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        err!(NightFuryError::UnsupportedScheduler)
+    }
+
+    fn fund(
+        &self,
+        _config: &NightFury,
+        _accounts: &SchedulerAccounts<'info>,
+        _amount: u64,
+    ) -> Result<()> {
+        err!(NightFuryError::UnsupportedScheduler)
+    }
+
+    fn withdraw(
+        &self,
+        _config: &NightFury,
+        _accounts: &SchedulerAccounts<'info>,
+        _amount: u64,
+    ) -> Result<()> {
+        err!(NightFuryError::UnsupportedScheduler)
+    }
+
+    fn stop(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        // The batch skips configs that are gone until they're removed from it.
+        Ok(())
+    }
+
+    fn crank(
+        &self,
+        _config: &NightFury,
+        _accounts: &CrankAccounts<'info>,
+        _now: i64,
+    ) -> Result<()> {
+        err!(NightFuryError::UnsupportedScheduler)
+    }
+}
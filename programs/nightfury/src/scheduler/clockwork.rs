@@ -0,0 +1,199 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program::{transfer, Transfer},
+};
+use clockwork_sdk::{
+    cpi::{
+        thread_create, thread_delete, thread_pause, thread_resume, thread_update, thread_withdraw,
+        ThreadCreate, ThreadDelete, ThreadPause, ThreadResume, ThreadUpdate, ThreadWithdraw,
+    },
+    state::{ThreadSettings, Trigger},
+};
+
+use super::{CrankAccounts, SchedulerAccounts, SchedulerBackend};
+use crate::errors::NightFuryError;
+use crate::state::{NightFury, ThreadConfig};
+
+/// A Clockwork thread owned by the config PDA, running `switch` on a cron trigger.
+pub struct Clockwork;
+
+impl Clockwork {
+    fn update(
+        config: &NightFury,
+        accounts: &SchedulerAccounts,
+        settings: ThreadSettings,
+    ) -> Result<()> {
+        thread_update(
+            CpiContext::new_with_signer(
+                accounts.thread_program.clone(),
+                ThreadUpdate {
+                    authority: accounts.nightfury.clone(),
+                    system_program: accounts.system_program.clone(),
+                    thread: accounts.thread.clone(),
+                },
+                &[&config.signer_seeds()],
+            ),
+            settings,
+        )
+    }
+
+    fn trigger(config: &NightFury) -> Trigger {
+        Trigger::Cron {
+            schedule: config.schedule.clone(),
+            skippable: config.thread_config.skippable,
+        }
+    }
+}
+
+impl<'info> SchedulerBackend<'info> for Clockwork {
+    fn start(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        switch_instruction: Instruction,
+        funding: u64,
+    ) -> Result<()> {
+        thread_create(
+            CpiContext::new_with_signer(
+                accounts.thread_program.clone(),
+                ThreadCreate {
+                    payer: accounts.authority.clone(),
+                    system_program: accounts.system_program.clone(),
+                    thread: accounts.thread.clone(),
+                    authority: accounts.nightfury.clone(),
+                },
+                &[&config.signer_seeds()],
+            ),
+            funding,
+            config.thread_id.clone(),
+            vec![switch_instruction.into()],
+            Self::trigger(config),
+        )?;
+
+        // New threads come with the defaults, anything else takes an update.
+        if config.thread_config != ThreadConfig::default() {
+            self.configure(config, accounts)?;
+        }
+
+        Ok(())
+    }
+
+    fn reschedule(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        Self::update(
+            config,
+            accounts,
+            ThreadSettings {
+                fee: None,
+                instructions: None,
+                name: None,
+                rate_limit: None,
+                trigger: Some(Self::trigger(config)),
+            },
+        )
+    }
+
+    fn configure(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        let thread_config = &config.thread_config;
+        Self::update(
+            config,
+            accounts,
+            ThreadSettings {
+                fee: Some(thread_config.fee),
+                instructions: None,
+                name: Some(thread_config.name.clone()),
+                rate_limit: Some(thread_config.rate_limit),
+                trigger: Some(Self::trigger(config)),
+            },
+        )
+    }
+
+    fn pause(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        // Stop the thread so paused configs don't pay for cranks.
+        thread_pause(CpiContext::new_with_signer(
+            accounts.thread_program.clone(),
+            ThreadPause {
+                authority: accounts.nightfury.clone(),
+                thread: accounts.thread.clone(),
+            },
+            &[&config.signer_seeds()],
+        ))
+    }
+
+    fn resume(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        thread_resume(CpiContext::new_with_signer(
+            accounts.thread_program.clone(),
+            ThreadResume {
+                authority: accounts.nightfury.clone(),
+                thread: accounts.thread.clone(),
+            },
+            &[&config.signer_seeds()],
+        ))
+    }
+
+    fn fund(
+        &self,
+        _config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        // Clockwork has no deposit instruction, threads pay fees out of their own balance.
+        transfer(
+            CpiContext::new(
+                accounts.system_program.clone(),
+                Transfer {
+                    from: accounts.authority.clone(),
+                    to: accounts.thread.clone(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    fn withdraw(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        thread_withdraw(
+            CpiContext::new_with_signer(
+                accounts.thread_program.clone(),
+                ThreadWithdraw {
+                    authority: accounts.nightfury.clone(),
+                    pay_to: accounts.authority.clone(),
+                    thread: accounts.thread.clone(),
+                },
+                &[&config.signer_seeds()],
+            ),
+            amount,
+        )
+    }
+
+    fn stop(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        // The thread deletes itself once the config finishes.
+        if accounts.thread.data_is_empty() {
+            return Ok(());
+        }
+
+        thread_delete(CpiContext::new_with_signer(
+            accounts.thread_program.clone(),
+            ThreadDelete {
+                authority: accounts.nightfury.clone(),
+                close_to: accounts.authority.clone(),
+                thread: accounts.thread.clone(),
+            },
+            &[&config.signer_seeds()],
+        ))
+    }
+
+    fn crank(&self, config: &NightFury, accounts: &CrankAccounts<'info>, _now: i64) -> Result<()> {
+        require_keys_eq!(
+            accounts.signer.key(),
+            config.thread,
+            NightFuryError::InvalidThread
+        );
+
+        Ok(())
+    }
+}
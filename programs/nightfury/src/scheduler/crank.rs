@@ -0,0 +1,246 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::Instruction,
+    system_program::{transfer, Transfer},
+};
+
+use super::{CrankAccounts, SchedulerAccounts, SchedulerBackend};
+use crate::errors::NightFuryError;
+use crate::state::NightFury;
+
+/// Permissionless cranking: anyone may call `switch` once the config's schedule is due. Cranks
+/// that update the metadata are paid `bounty` lamports out of the config's escrow PDA, a
+/// plain system account; ones that find nothing to do aren't, so a frequent schedule can't
+/// be used to drain the escrow.
+pub struct Crank {
+    pub bounty: u64,
+}
+
+impl Crank {
+    /// Whether a config may be cranked at unix time `now`.
+    fn check_due(config: &NightFury, now: i64) -> Result<()> {
+        require!(now >= config.next_switch_at, NightFuryError::CrankNotDue);
+
+        Ok(())
+    }
+
+    /// Lamports an escrow holding `balance` pays a crank, leaving `rent_exempt` behind. Nothing
+    /// at all if it can't cover the whole bounty.
+    fn payout(&self, balance: u64, rent_exempt: u64) -> u64 {
+        match self.bounty.checked_add(rent_exempt) {
+            Some(needed) if balance >= needed => self.bounty,
+            _ => 0,
+        }
+    }
+
+    /// Checks topping an escrow holding `balance` up by `amount` leaves it rent exempt, as
+    /// the system program won't create it otherwise.
+    fn check_funding(balance: u64, amount: u64, rent_exempt: u64) -> Result<()> {
+        require!(
+            balance.saturating_add(amount) >= rent_exempt,
+            NightFuryError::InvalidFunding
+        );
+
+        Ok(())
+    }
+
+    /// Lamports the authority may take out of an escrow holding `balance`, leaving it rent
+    /// exempt; revoking is what empties it.
+    fn withdrawable(balance: u64, rent_exempt: u64) -> u64 {
+        balance.saturating_sub(rent_exempt)
+    }
+
+    /// Pays `amount` out of the escrow back to the authority.
+    fn refund(accounts: &SchedulerAccounts, amount: u64) -> Result<()> {
+        transfer(
+            CpiContext::new_with_signer(
+                accounts.system_program.clone(),
+                Transfer {
+                    from: accounts.escrow.clone(),
+                    to: accounts.authority.clone(),
+                },
+                &[&[
+                    b"escrow".as_ref(),
+                    accounts.nightfury.key.as_ref(),
+                    &[accounts.escrow_bump],
+                ]],
+            ),
+            amount,
+        )
+    }
+}
+
+impl<'info> SchedulerBackend<'info> for Crank {
+    fn start(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        _switch_instruction: Instruction,
+        funding: u64,
+    ) -> Result<()> {
+        if funding == 0 {
+            return Ok(());
+        }
+
+        self.fund(config, accounts, funding)
+    }
+
+    fn fund(
+        &self,
+        _config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        Self::check_funding(
+            accounts.escrow.lamports(),
+            amount,
+            Rent::get()?.minimum_balance(0),
+        )?;
+
+        transfer(
+            CpiContext::new(
+                accounts.system_program.clone(),
+                Transfer {
+                    from: accounts.authority.clone(),
+                    to: accounts.escrow.clone(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    fn withdraw(
+        &self,
+        _config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let available =
+            Self::withdrawable(accounts.escrow.lamports(), Rent::get()?.minimum_balance(0));
+        require!(amount <= available, NightFuryError::InsufficientFunds);
+
+        Self::refund(accounts, amount)
+    }
+
+    fn stop(&self, _config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        // Return whatever is left of the bounties.
+        let balance = accounts.escrow.lamports();
+        if balance == 0 {
+            return Ok(());
+        }
+
+        Self::refund(accounts, balance)
+    }
+
+    fn crank(&self, config: &NightFury, _accounts: &CrankAccounts<'info>, now: i64) -> Result<()> {
+        Self::check_due(config, now)
+    }
+
+    fn reward(&self, _config: &NightFury, accounts: &CrankAccounts<'info>) -> Result<()> {
+        // Pay the cranker, keeping the escrow rent exempt.
+        let escrow = &accounts.escrow;
+        let payout = self.payout(escrow.lamports(), Rent::get()?.minimum_balance(0));
+        if payout == 0 {
+            msg!("escrow can't cover the bounty");
+            return Ok(());
+        }
+
+        transfer(
+            CpiContext::new_with_signer(
+                accounts.system_program.clone(),
+                Transfer {
+                    from: escrow.clone(),
+                    to: accounts.signer.clone(),
+                },
+                &[&[
+                    b"escrow".as_ref(),
+                    accounts.nightfury.as_ref(),
+                    &[accounts.escrow_bump],
+                ]],
+            ),
+            payout,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{test_config, Phase};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn rent_exempt() -> u64 {
+        Rent::default().minimum_balance(0)
+    }
+
+    #[test]
+    fn cranks_are_only_accepted_once_due() {
+        let nightfury = NightFury {
+            next_switch_at: NOW,
+            ..test_config(2)
+        };
+        assert_eq!(
+            Crank::check_due(&nightfury, NOW - 1).unwrap_err(),
+            NightFuryError::CrankNotDue.into()
+        );
+        assert!(Crank::check_due(&nightfury, NOW).is_ok());
+        assert!(Crank::check_due(&nightfury, NOW + 3600).is_ok());
+    }
+
+    #[test]
+    fn payouts_leave_the_escrow_rent_exempt() {
+        let crank = Crank { bounty: 5_000 };
+        let rent_exempt = rent_exempt();
+        assert_eq!(crank.payout(rent_exempt + 5_000, rent_exempt), 5_000);
+        assert_eq!(crank.payout(rent_exempt + 1_000_000, rent_exempt), 5_000);
+        // No partial bounties out of what keeps the escrow alive.
+        assert_eq!(crank.payout(rent_exempt + 4_999, rent_exempt), 0);
+        assert_eq!(crank.payout(0, rent_exempt), 0);
+        assert_eq!(Crank { bounty: u64::MAX }.payout(u64::MAX, rent_exempt), 0);
+    }
+
+    #[test]
+    fn cranks_that_change_nothing_are_not_paid() {
+        // `switch` only rewards a crank that had to update the metadata.
+        let phase = Phase {
+            uri: "test.com/day".to_string(),
+            name: Some("Day".to_string()),
+            symbol: None,
+        };
+        let data = mpl_token_metadata::state::Data {
+            name: "Night".to_string(),
+            symbol: "NIGHT".to_string(),
+            uri: "test.com/night".to_string(),
+            seller_fee_basis_points: 500,
+            creators: None,
+        };
+        assert!(!phase.is_applied(&data));
+
+        // Metadata strings come back padded with nulls.
+        let mut applied = phase.apply(data);
+        applied.uri = format!("{:\0<200}", applied.uri);
+        applied.name = format!("{:\0<32}", applied.name);
+        assert!(phase.is_applied(&applied));
+    }
+
+    #[test]
+    fn funding_has_to_make_the_escrow_rent_exempt() {
+        let rent_exempt = rent_exempt();
+        assert_eq!(
+            Crank::check_funding(0, rent_exempt - 1, rent_exempt).unwrap_err(),
+            NightFuryError::InvalidFunding.into()
+        );
+        assert!(Crank::check_funding(0, rent_exempt, rent_exempt).is_ok());
+        // Once exempt, any top-up goes.
+        assert!(Crank::check_funding(rent_exempt, 1, rent_exempt).is_ok());
+    }
+
+    #[test]
+    fn withdrawals_leave_the_escrow_rent_exempt() {
+        let rent_exempt = rent_exempt();
+        assert_eq!(Crank::withdrawable(rent_exempt + 5_000, rent_exempt), 5_000);
+        assert_eq!(Crank::withdrawable(rent_exempt, rent_exempt), 0);
+        assert_eq!(Crank::withdrawable(0, rent_exempt), 0);
+    }
+}
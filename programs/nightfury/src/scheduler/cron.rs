@@ -0,0 +1,113 @@
+//! Cron schedules, parsed with the same engine the Clockwork thread program fires them
+//! with, so a schedule accepted here is one the thread will actually run.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use clockwork_cron::Schedule;
+
+use crate::errors::NightFuryError;
+
+pub const MAX_SCHEDULE_LENGTH: usize = 64;
+
+/// Checks `schedule` is a valid cron expression, seconds through years, that still fires
+/// after unix time `now`.
+pub fn validate(schedule: &str, now: i64) -> Result<()> {
+    require!(
+        schedule.len() <= MAX_SCHEDULE_LENGTH,
+        NightFuryError::ScheduleTooLong
+    );
+    require!(
+        Schedule::from_str(schedule).is_ok(),
+        NightFuryError::InvalidSchedule
+    );
+    require!(
+        next_fire(schedule, now).is_some(),
+        NightFuryError::ScheduleExhausted
+    );
+
+    Ok(())
+}
+
+/// Unix time `schedule` next fires at, strictly after `after`.
+pub fn next_fire(schedule: &str, after: i64) -> Option<i64> {
+    let after = NaiveDateTime::from_timestamp_opt(after, 0)?;
+    Schedule::from_str(schedule)
+        .ok()?
+        .next_after(&DateTime::<Utc>::from_utc(after, Utc))
+        .map(|datetime| datetime.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2023-01-01T00:00:00Z.
+    const NEW_YEAR_2023: i64 = 1_672_531_200;
+
+    #[test]
+    fn next_fire_is_strictly_after() {
+        let hourly = "0 0 * * * * *";
+        assert_eq!(
+            next_fire(hourly, NEW_YEAR_2023),
+            Some(NEW_YEAR_2023 + 3_600)
+        );
+        assert_eq!(next_fire(hourly, NEW_YEAR_2023 - 1), Some(NEW_YEAR_2023));
+    }
+
+    #[test]
+    fn next_fire_follows_the_calendar() {
+        // Noon on the first of every month, from the middle of January.
+        let monthly = "0 0 12 1 * * *";
+        assert_eq!(
+            next_fire(monthly, NEW_YEAR_2023 + 14 * 86_400),
+            Some(NEW_YEAR_2023 + 31 * 86_400 + 12 * 3_600)
+        );
+    }
+
+    #[test]
+    fn accepts_a_valid_schedule() {
+        assert!(validate("0 */10 * * * * *", NEW_YEAR_2023).is_ok());
+    }
+
+    #[test]
+    fn rejects_schedules_over_the_max_length() {
+        let schedule = format!("0 0 * * * * {}", "2030,".repeat(12) + "2031");
+        assert!(schedule.len() > MAX_SCHEDULE_LENGTH);
+        assert_eq!(
+            validate(&schedule, NEW_YEAR_2023).unwrap_err(),
+            NightFuryError::ScheduleTooLong.into()
+        );
+
+        let schedule = format!("0 0 * * * * {}", "2030,".repeat(9) + "2031");
+        assert!(schedule.len() <= MAX_SCHEDULE_LENGTH);
+        assert!(validate(&schedule, NEW_YEAR_2023).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        for schedule in ["", "every day", "0 0 25 * * * *", "* * * * *"] {
+            assert_eq!(
+                validate(schedule, NEW_YEAR_2023).unwrap_err(),
+                NightFuryError::InvalidSchedule.into(),
+                "{schedule:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_schedules_with_no_future_fire() {
+        let schedule = "0 0 0 1 1 * 2022";
+        assert_eq!(next_fire(schedule, NEW_YEAR_2023), None);
+        assert_eq!(
+            validate(schedule, NEW_YEAR_2023).unwrap_err(),
+            NightFuryError::ScheduleExhausted.into()
+        );
+
+        // The last fire itself doesn't count either.
+        let schedule = "0 0 0 1 1 * 2023";
+        assert_eq!(next_fire(schedule, NEW_YEAR_2023 - 1), Some(NEW_YEAR_2023));
+        assert_eq!(next_fire(schedule, NEW_YEAR_2023), None);
+    }
+}
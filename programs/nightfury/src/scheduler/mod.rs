@@ -0,0 +1,139 @@
+//! Backends that crank `switch`, chosen per config at init and recorded as its
+//! [`Scheduler`]. Instructions go through [`SchedulerBackend`] and never care which one
+//! drives a given config.
+
+mod batch;
+mod clockwork;
+mod crank;
+pub mod cron;
+
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+
+use crate::errors::NightFuryError;
+use crate::state::{Batch, Collection, NightFury, Scheduler};
+
+/// Accounts every scheduler instruction passes, whichever backend it ends up using.
+pub struct SchedulerAccounts<'info> {
+    pub nightfury: AccountInfo<'info>,
+    /// Pays for and gets refunded whatever the backend holds.
+    pub authority: AccountInfo<'info>,
+    pub thread: AccountInfo<'info>,
+    pub thread_program: AccountInfo<'info>,
+    pub escrow: AccountInfo<'info>,
+    pub escrow_bump: u8,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Accounts `switch` hands the backend to check and reward its caller.
+pub struct CrankAccounts<'info> {
+    pub nightfury: Pubkey,
+    pub signer: AccountInfo<'info>,
+    pub escrow: AccountInfo<'info>,
+    pub escrow_bump: u8,
+    pub system_program: AccountInfo<'info>,
+}
+
+pub trait SchedulerBackend<'info> {
+    /// Starts cranking `switch_instruction` for a freshly initialized config.
+    fn start(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        switch_instruction: Instruction,
+        funding: u64,
+    ) -> Result<()>;
+
+    /// Moves the backend onto the config's newly updated schedule. Nothing to do for
+    /// backends that go by `next_switch_at` unless overridden.
+    fn reschedule(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Applies the config's thread settings to the backend.
+    fn configure(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        err!(NightFuryError::UnsupportedScheduler)
+    }
+
+    /// Stops cranking until `resume`. The config status alone is enough unless overridden.
+    fn pause(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        Ok(())
+    }
+
+    fn resume(&self, _config: &NightFury, _accounts: &SchedulerAccounts<'info>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Tops up what the backend pays cranks out of with `amount` lamports from the authority.
+    fn fund(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()>;
+
+    /// Returns `amount` lamports of the backend's balance to the authority.
+    fn withdraw(
+        &self,
+        config: &NightFury,
+        accounts: &SchedulerAccounts<'info>,
+        amount: u64,
+    ) -> Result<()>;
+
+    /// Tears the backend down for good, returning what it holds to the authority.
+    fn stop(&self, config: &NightFury, accounts: &SchedulerAccounts<'info>) -> Result<()>;
+
+    /// Checks the caller of `switch` may crank the config at unix time `now`.
+    fn crank(&self, config: &NightFury, accounts: &CrankAccounts<'info>, now: i64) -> Result<()>;
+
+    /// Pays the caller of `switch` for a crank that updated the metadata, where the backend
+    /// rewards cranks.
+    fn reward(&self, _config: &NightFury, _accounts: &CrankAccounts<'info>) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Scheduler {
+    pub fn backend<'info>(&self) -> Box<dyn SchedulerBackend<'info>> {
+        match self {
+            Scheduler::Clockwork => Box::new(clockwork::Clockwork),
+            Scheduler::Crank { bounty } => Box::new(crank::Crank { bounty: *bounty }),
+            Scheduler::Batch { .. } => Box::new(batch::Batched),
+        }
+    }
+}
+
+impl NightFury {
+    /// Seeds the config PDA signs with.
+    pub fn signer_seeds(&self) -> [&[u8]; 5] {
+        [
+            b"nightfury".as_ref(),
+            self.mint.as_ref(),
+            self.authority.as_ref(),
+            self.thread_id.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Batch {
+    /// Seeds the batch PDA signs for its thread with.
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            b"batch".as_ref(),
+            self.authority.as_ref(),
+            self.thread_id.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
+
+impl Collection {
+    /// Seeds the collection PDA signs for its thread and its members' metadata with.
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            b"collection".as_ref(),
+            self.collection_mint.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+}
@@ -1,24 +1,995 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+use mpl_token_metadata::state::{Data, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+
+use crate::errors::NightFuryError;
+use crate::scheduler::cron::MAX_SCHEDULE_LENGTH;
 
 #[account]
 pub struct NightFury {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub phases: Vec<Phase>,
+    pub phase: u8,
+    pub phase_changed_at: i64,
+    pub mode: PhaseMode,
+    pub timezone: Timezone,
+    pub status: NightFuryStatus,
+    pub expiry: Option<Expiry>,
+    /// Number of phase changes made so far, counted towards `expiry.max_switches`.
+    pub switch_count: u32,
+    pub holder_switch: Option<HolderSwitchConfig>,
+    /// Phase picked by the holder, shown until the schedule next moves on.
+    pub manual_phase: Option<u8>,
+    pub manual_switched_at: i64,
+    pub pin: Option<Pin>,
+    pub scheduler: Scheduler,
+    /// Cron schedule `switch` is cranked on, seconds through years, in UTC.
+    pub schedule: String,
+    pub thread_config: ThreadConfig,
+    /// Unix time the schedule next fires at, enforced by schedulers that need it.
+    pub next_switch_at: i64,
+    pub thread: Pubkey,
+    pub thread_id: Vec<u8>,
+    pub bump: u8,
+}
+
+impl NightFury {
+    pub const MAX_PHASES: usize = 8;
+    pub const MAX_THREAD_ID_LENGTH: usize = 32;
+
+    /// Account size for a config holding `phase_count` phases.
+    pub fn space(phase_count: usize) -> usize {
+        8 + 32
+            + 32
+            + 4
+            + phase_count * Phase::LENGTH
+            + 1
+            + 8
+            + PhaseMode::LENGTH
+            + Timezone::LENGTH
+            + 1
+            + 1
+            + Expiry::LENGTH
+            + 4
+            + 1
+            + HolderSwitchConfig::LENGTH
+            + 2
+            + 8
+            + 1
+            + Pin::LENGTH
+            + Scheduler::LENGTH
+            + 4
+            + MAX_SCHEDULE_LENGTH
+            + ThreadConfig::LENGTH
+            + 8
+            + 32
+            + 4
+            + Self::MAX_THREAD_ID_LENGTH
+            + 1
+    }
+
+    /// `thread_id` as a seed of the config and thread PDAs, rejected if Clockwork can't take
+    /// it. Init seeds are derived before any constraint runs, so this is where it's checked.
+    pub fn thread_id_seed(thread_id: &[u8]) -> Result<&[u8]> {
+        require!(
+            thread_id.len() <= Self::MAX_THREAD_ID_LENGTH,
+            NightFuryError::ThreadIdTooLong
+        );
+
+        Ok(thread_id)
+    }
+
+    /// System-owned PDA that crank bounties are paid out of.
+    pub fn escrow_pubkey(nightfury: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"escrow".as_ref(), nightfury.as_ref()], &crate::ID).0
+    }
+
+    pub fn validate_phases(phases: &[Phase]) -> Result<()> {
+        require!(
+            (2..=Self::MAX_PHASES).contains(&phases.len()),
+            NightFuryError::InvalidPhaseCount
+        );
+        for phase in phases {
+            phase.validate()?;
+        }
+        // Phases that don't set a name or symbol leave it as it is, so one set by only some
+        // phases would stick around through the others.
+        let all_or_none =
+            |is_set: fn(&Phase) -> bool| phases.iter().all(is_set) || !phases.iter().any(is_set);
+        require!(
+            all_or_none(|phase| phase.name.is_some())
+                && all_or_none(|phase| phase.symbol.is_some()),
+            NightFuryError::InconsistentPhases
+        );
+
+        Ok(())
+    }
+}
+
+/// A config created before phases replaced the day/night toggle. It carries `NightFury`'s
+/// discriminator but can't be read as one, so it's told apart by its size and only ever
+/// revoked. Its thread, not the config, holds the data delegate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyNightFury {
     pub authority: Pubkey,
     pub mint: Pubkey,
     pub day_uri: String,
     pub night_uri: String,
-    pub state: NightFuryState,
+    /// 0 for day, 1 for night.
+    pub state: u8,
     pub thread: Pubkey,
     pub thread_id: Vec<u8>,
     pub bump: u8,
 }
 
-impl NightFury {
-    pub const MAX_URI_LENGTH: u16 = 256;
+impl LegacyNightFury {
+    /// Size every legacy config was created with.
     pub const LENGTH: usize = 8 + 32 + 256 + 256 + 3 + 1;
+
+    /// Reads a legacy config out of a program-owned `account`.
+    pub fn try_from_account(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        require!(
+            account.owner == &crate::ID
+                && data.len() == Self::LENGTH
+                && data[..8] == NightFury::discriminator(),
+            NightFuryError::InvalidLegacyConfig
+        );
+
+        Self::deserialize(&mut &data[8..]).map_err(|_| NightFuryError::InvalidLegacyConfig.into())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
-pub enum NightFuryState {
-    Day,
-    Night,
+pub enum NightFuryStatus {
+    Active,
+    /// Scheduled switches are suspended; the delegate stays in place for a free resume.
+    Paused,
+    /// The expiry was reached: the final phase is shown and the thread is gone.
+    Finished,
+}
+
+/// When a config stops switching on its own, e.g. at the end of a time-limited event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Expiry {
+    /// Unix time after which the config finishes.
+    pub end_at: Option<i64>,
+    /// Number of phase changes after which the config finishes.
+    pub max_switches: Option<u32>,
+    /// Index of the phase left on the NFT once finished.
+    pub final_phase: u8,
+}
+
+impl Expiry {
+    pub const LENGTH: usize = 1 + 8 + 1 + 4 + 1;
+
+    pub fn validate(&self, phase_count: usize, now: i64) -> Result<()> {
+        require!(
+            (self.final_phase as usize) < phase_count,
+            NightFuryError::InvalidPhaseIndex
+        );
+        require!(
+            self.end_at.is_some() || self.max_switches.is_some(),
+            NightFuryError::InvalidExpiry
+        );
+        require!(
+            self.end_at.iter().all(|&end_at| end_at > now)
+                && self.max_switches.iter().all(|&max| max > 0),
+            NightFuryError::InvalidExpiry
+        );
+
+        Ok(())
+    }
+
+    /// Whether the config should finish at unix time `now` after `switch_count` changes.
+    pub fn is_reached(&self, now: i64, switch_count: u32) -> bool {
+        self.end_at.iter().any(|&end_at| now >= end_at)
+            || self.max_switches.iter().any(|&max| switch_count >= max)
+    }
+}
+
+/// What cranks `switch`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum Scheduler {
+    /// A Clockwork thread on the configured cron schedule.
+    Clockwork,
+    /// Anyone, whenever the cron schedule is due, for a `bounty` in lamports paid from the
+    /// config's escrow. Keeps working without any off-chain service network.
+    Crank { bounty: u64 },
+    /// The thread of a shared [`Batch`], switching many configs on the batch's schedule.
+    Batch { batch: Pubkey },
+}
+
+impl Scheduler {
+    pub const LENGTH: usize = 1 + 32;
+}
+
+/// One Clockwork thread switching many configs, a page of them per transaction, so a
+/// collection doesn't need a funded thread per NFT.
+#[account]
+pub struct Batch {
+    pub authority: Pubkey,
+    pub thread: Pubkey,
+    pub thread_id: Vec<u8>,
+    /// Authorization rules shared by the members' metadata.
+    pub auth_rules: Pubkey,
+    /// Cron schedule the whole batch is switched on.
+    pub schedule: String,
+    /// Configs switched per transaction, kept low enough to fit the compute budget.
+    pub page_size: u8,
+    pub members: Vec<BatchMember>,
+    pub bump: u8,
+}
+
+impl Batch {
+    pub const MAX_MEMBERS: usize = 128;
+    /// Pages have to fit in a single legacy transaction along with the thread's accounts.
+    pub const MAX_PAGE_SIZE: u8 = 4;
+    pub const LENGTH: usize = 8
+        + 32
+        + 32
+        + 4
+        + NightFury::MAX_THREAD_ID_LENGTH
+        + 32
+        + 4
+        + MAX_SCHEDULE_LENGTH
+        + 1
+        + 4
+        + Self::MAX_MEMBERS * BatchMember::LENGTH
+        + 1;
+
+    /// The member slots on page `page`, vacant ones included.
+    pub fn page(&self, page: u32) -> &[BatchMember] {
+        let start = (page as usize)
+            .saturating_mul(self.page_size as usize)
+            .min(self.members.len());
+        let end = start
+            .saturating_add(self.page_size as usize)
+            .min(self.members.len());
+        &self.members[start..end]
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct BatchMember {
+    pub nightfury: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl BatchMember {
+    pub const LENGTH: usize = 32 + 32;
+    /// Left in a removed member's slot, so the members after it keep their pages while a run
+    /// may be in flight.
+    pub const VACANT: Self = Self {
+        nightfury: Pubkey::new_from_array([0; 32]),
+        mint: Pubkey::new_from_array([0; 32]),
+    };
+
+    pub fn is_vacant(&self) -> bool {
+        *self == Self::VACANT
+    }
+}
+
+/// Clockwork thread settings, ignored by other schedulers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ThreadConfig {
+    /// Lamports paid to the Clockwork worker per execution.
+    pub fee: u64,
+    /// Maximum executions per slot.
+    pub rate_limit: u64,
+    pub name: String,
+    /// Skip triggers missed while the network was busy rather than catching up on them.
+    pub skippable: bool,
+}
+
+impl ThreadConfig {
+    /// Lowest fee the thread program accepts, and what new threads start with.
+    pub const MIN_FEE: u64 = 1_000;
+    /// Keeps a fat-fingered fee from draining the thread in a handful of executions.
+    pub const MAX_FEE: u64 = 10_000_000;
+    pub const MAX_NAME_LENGTH: usize = 32;
+    pub const LENGTH: usize = 8 + 8 + 4 + Self::MAX_NAME_LENGTH + 1;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (Self::MIN_FEE..=Self::MAX_FEE).contains(&self.fee),
+            NightFuryError::InvalidThreadFee
+        );
+        require!(self.rate_limit > 0, NightFuryError::InvalidRateLimit);
+        require!(
+            self.name.len() <= Self::MAX_NAME_LENGTH,
+            NightFuryError::NameTooLong
+        );
+
+        Ok(())
+    }
+}
+
+/// What the thread program gives new threads.
+impl Default for ThreadConfig {
+    fn default() -> Self {
+        Self {
+            fee: Self::MIN_FEE,
+            rate_limit: u64::MAX,
+            name: String::new(),
+            skippable: true,
+        }
+    }
+}
+
+/// Phases, schedule and thread shared by every member of an NFT collection, keyed by the
+/// collection mint. Members only get a small [`CollectionMember`] each and delegate to this
+/// PDA, so collection-wide changes are a single instruction.
+#[account]
+pub struct Collection {
+    pub authority: Pubkey,
+    pub collection_mint: Pubkey,
+    pub phases: Vec<Phase>,
+    pub phase: u8,
+    pub phase_changed_at: i64,
+    pub mode: PhaseMode,
+    pub timezone: Timezone,
+    /// Cron schedule the whole collection is switched on.
+    pub schedule: String,
+    /// Authorization rules shared by the members' metadata.
+    pub auth_rules: Pubkey,
+    /// Members switched per transaction, kept low enough to fit the compute budget.
+    pub page_size: u8,
+    /// Index the next member registers under. Removed members leave gaps behind.
+    pub next_index: u32,
+    pub member_count: u32,
+    pub thread: Pubkey,
+    pub thread_id: Vec<u8>,
+    pub bump: u8,
+}
+
+impl Collection {
+    /// Pages have to fit in a single legacy transaction along with the thread's accounts.
+    pub const MAX_PAGE_SIZE: u8 = 4;
+
+    /// Account size for a collection holding `phase_count` phases.
+    pub fn space(phase_count: usize) -> usize {
+        8 + 32
+            + 32
+            + 4
+            + phase_count * Phase::LENGTH
+            + 1
+            + 8
+            + PhaseMode::LENGTH
+            + Timezone::LENGTH
+            + 4
+            + MAX_SCHEDULE_LENGTH
+            + 32
+            + 1
+            + 4
+            + 4
+            + 32
+            + 4
+            + NightFury::MAX_THREAD_ID_LENGTH
+            + 1
+    }
+
+    pub fn pubkey(collection_mint: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"collection".as_ref(), collection_mint.as_ref()],
+            &crate::ID,
+        )
+        .0
+    }
+
+    /// Member indices switched by page `page`, including gaps.
+    pub fn page(&self, page: u32) -> std::ops::Range<u32> {
+        let start = page
+            .saturating_mul(self.page_size.into())
+            .min(self.next_index);
+        let end = start
+            .saturating_add(self.page_size.into())
+            .min(self.next_index);
+        start..end
+    }
+}
+
+/// A mint registered with a [`Collection`], under the collection's next free index.
+#[account]
+pub struct CollectionMember {
+    pub collection: Pubkey,
+    pub mint: Pubkey,
+    pub index: u32,
+    pub bump: u8,
+}
+
+impl CollectionMember {
+    pub const LENGTH: usize = 8 + 32 + 32 + 4 + 1;
+
+    pub fn pubkey(collection: Pubkey, index: u32) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"member".as_ref(),
+                collection.as_ref(),
+                index.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        )
+        .0
+    }
+}
+
+/// A phase the authority holds in place, whatever the schedule says.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Pin {
+    pub phase: u8,
+    /// Unix time the pin runs out at, after which the schedule takes over again.
+    pub until: i64,
+}
+
+impl Pin {
+    pub const LENGTH: usize = 1 + 8;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.until
+    }
+}
+
+/// Lets the token holder pick the phase of their own NFT on demand.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct HolderSwitchConfig {
+    /// Minimum seconds between two holder switches.
+    pub cooldown: i64,
+    /// Lamports the holder pays the authority per switch.
+    pub fee: u64,
+}
+
+impl HolderSwitchConfig {
+    pub const LENGTH: usize = 8 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.cooldown >= 0, NightFuryError::InvalidCooldown);
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Phase {
+    pub uri: String,
+    /// Metadata name to show during this phase, left untouched when `None`.
+    pub name: Option<String>,
+    /// Metadata symbol to show during this phase, left untouched when `None`.
+    pub symbol: Option<String>,
+}
+
+impl Phase {
+    pub const LENGTH: usize =
+        4 + MAX_URI_LENGTH + 1 + 4 + MAX_NAME_LENGTH + 1 + 4 + MAX_SYMBOL_LENGTH;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.uri.len() <= MAX_URI_LENGTH, NightFuryError::UriTooLong);
+        if let Some(name) = &self.name {
+            require!(name.len() <= MAX_NAME_LENGTH, NightFuryError::NameTooLong);
+        }
+        if let Some(symbol) = &self.symbol {
+            require!(
+                symbol.len() <= MAX_SYMBOL_LENGTH,
+                NightFuryError::SymbolTooLong
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether `data` already shows this phase.
+    pub fn is_applied(&self, data: &Data) -> bool {
+        let matches = |value: &str, current: &str| value == current.trim_end_matches('\0');
+
+        matches(&self.uri, &data.uri)
+            && self.name.iter().all(|n| matches(n, &data.name))
+            && self.symbol.iter().all(|s| matches(s, &data.symbol))
+    }
+
+    /// Metaplex data with this phase applied on top of `data`.
+    pub fn apply(&self, data: Data) -> Data {
+        Data {
+            name: self.name.clone().unwrap_or(data.name),
+            symbol: self.symbol.clone().unwrap_or(data.symbol),
+            uri: self.uri.clone(),
+            ..data
+        }
+    }
+}
+
+/// Dated phases that take priority over the regular schedule, e.g. holiday art.
+#[account]
+pub struct Overrides {
+    pub nightfury: Pubkey,
+    pub entries: Vec<Override>,
+    pub bump: u8,
+}
+
+impl Overrides {
+    pub const MAX_ENTRIES: usize = 8;
+    pub const LENGTH: usize = 8 + 32 + 4 + Self::MAX_ENTRIES * Override::LENGTH + 1;
+
+    pub fn pubkey(nightfury: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"overrides".as_ref(), nightfury.as_ref()], &crate::ID).0
+    }
+
+    /// The override covering unix time `now`, if any.
+    pub fn active(&self, now: i64) -> Option<&Override> {
+        self.entries
+            .iter()
+            .find(|entry| (entry.start_at..entry.end_at).contains(&now))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Override {
+    /// Unix time the override starts at, inclusive.
+    pub start_at: i64,
+    /// Unix time the override ends at, exclusive.
+    pub end_at: i64,
+    pub phase: Phase,
+}
+
+impl Override {
+    pub const LENGTH: usize = 8 + 8 + Phase::LENGTH;
+}
+
+/// How the active phase is derived from the cluster clock.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum PhaseMode {
+    /// Step through the phases in order, each lasting `period` seconds. Phase 0 starts at
+    /// `offset` seconds past the local epoch (and every full cycle after that), so an offset
+    /// of 20:00 with a 12 hour period follows the configured timezone, DST included.
+    Cycle { period: i64, offset: i64 },
+    /// Follow the sun at `latitude`/`longitude` (micro-degrees, east positive), optionally
+    /// corrected for an observer `altitude` in meters. Two phases are day and night; four
+    /// phases are dawn, day, dusk and night, with civil twilight as dawn and dusk.
+    Solar {
+        latitude: i32,
+        longitude: i32,
+        altitude: Option<u32>,
+    },
+    /// Follow the moon through eight phases: new, waxing crescent, first quarter, waxing
+    /// gibbous, full, waning gibbous, last quarter and waning crescent. A daily cron is
+    /// enough to keep up with it.
+    Lunar,
+    /// Follow the meteorological seasons in local time: spring, summer, autumn and winter.
+    /// Southern hemisphere configs see them six months apart from northern ones.
+    Seasonal { hemisphere: Hemisphere },
+    /// Follow a Pyth-style price `feed`, refusing prices published more than `max_age`
+    /// seconds ago. `thresholds` are ascending prices in the feed's fixed-point units, one
+    /// fewer than the phases: the phase index is the number of thresholds the price is at or
+    /// above, so `[X]` shows phase 1 from X upwards.
+    PriceFeed {
+        feed: Pubkey,
+        max_age: u32,
+        thresholds: Vec<i64>,
+    },
+    /// Follow a generic data `feed` (see `oracle::data_feed` for the layout), refusing values
+    /// older than `max_age` seconds. The phase comes from the last of the ascending `rules`
+    /// whose `min_value` the value reaches, e.g. weather codes mapped to rainy and clear art.
+    DataFeed {
+        feed: Pubkey,
+        max_age: u32,
+        rules: Vec<FeedRule>,
+    },
+    /// Grow through the phases in order, e.g. egg, hatchling and adult, spending at least
+    /// `stage_duration` seconds in each. Never goes back, and finishes on the last stage.
+    Evolution { stage_duration: i64 },
+    /// Show a placeholder (phase 0) until `reveal_at`, then the final art (phase 1) for good.
+    /// The thread deletes itself once revealed and the config can be revoked and closed.
+    Reveal { reveal_at: i64 },
+}
+
+impl PhaseMode {
+    pub const MAX_FEED_RULES: usize = 8;
+    /// Sized for the largest variant, `DataFeed`.
+    pub const LENGTH: usize = 1 + 32 + 4 + 4 + Self::MAX_FEED_RULES * FeedRule::LENGTH;
+
+    /// The oracle account `switch` has to be handed, if the mode reads one.
+    pub fn oracle(&self) -> Option<Pubkey> {
+        match self {
+            PhaseMode::PriceFeed { feed, .. } | PhaseMode::DataFeed { feed, .. } => Some(*feed),
+            _ => None,
+        }
+    }
+
+    /// Whether the mode only ever moves forward, so nothing may show an earlier phase.
+    pub fn is_one_way(&self) -> bool {
+        matches!(self, PhaseMode::Evolution { .. } | PhaseMode::Reveal { .. })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct FeedRule {
+    pub min_value: u64,
+    pub phase: u8,
+}
+
+impl FeedRule {
+    pub const LENGTH: usize = 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Hemisphere {
+    Northern,
+    Southern,
+}
+
+/// Local time used by wall-clock phase modes. Clockwork evaluates crons in UTC, so the
+/// schedule only needs to crank often enough; the phase itself follows local time.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Timezone {
+    /// Standard time offset from UTC in seconds.
+    pub utc_offset: i32,
+    pub dst: DstRule,
+}
+
+impl Timezone {
+    pub const LENGTH: usize = 4 + 1;
+}
+
+/// Daylight saving rules for a handful of major zones, each adding one hour while active.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DstRule {
+    None,
+    NorthAmerica,
+    Europe,
+    Australia,
+    NewZealand,
+}
+
+/// An active config cycling hourly through `phase_count` phases, with nothing else set, for
+/// unit tests to adjust.
+#[cfg(test)]
+pub fn test_config(phase_count: usize) -> NightFury {
+    NightFury {
+        authority: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        phases: (0..phase_count)
+            .map(|index| Phase {
+                uri: format!("test.com/{index}"),
+                name: None,
+                symbol: None,
+            })
+            .collect(),
+        phase: 0,
+        phase_changed_at: 0,
+        mode: PhaseMode::Cycle {
+            period: 3600,
+            offset: 0,
+        },
+        timezone: Timezone {
+            utc_offset: 0,
+            dst: DstRule::None,
+        },
+        status: NightFuryStatus::Active,
+        expiry: None,
+        switch_count: 0,
+        holder_switch: None,
+        manual_phase: None,
+        manual_switched_at: 0,
+        pin: None,
+        scheduler: Scheduler::Crank { bounty: 0 },
+        schedule: "0 0 * * * * *".to_string(),
+        thread_config: ThreadConfig::default(),
+        next_switch_at: 0,
+        thread: Pubkey::default(),
+        thread_id: b"test".to_vec(),
+        bump: 255,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_legacy(owner: Pubkey, mut data: Vec<u8>) -> Result<LegacyNightFury> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        LegacyNightFury::try_from_account(&account)
+    }
+
+    fn legacy_data() -> Vec<u8> {
+        let legacy = LegacyNightFury {
+            authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            day_uri: "test.com/day".to_string(),
+            night_uri: "test.com/night".to_string(),
+            state: 1,
+            thread: Pubkey::new_unique(),
+            thread_id: b"nightfury".to_vec(),
+            bump: 254,
+        };
+        let mut data = NightFury::discriminator().to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(LegacyNightFury::LENGTH, 0);
+        data
+    }
+
+    #[test]
+    fn legacy_configs_are_told_apart_by_size() {
+        for phase_count in 2..=NightFury::MAX_PHASES {
+            assert_ne!(NightFury::space(phase_count), LegacyNightFury::LENGTH);
+        }
+    }
+
+    #[test]
+    fn reads_legacy_configs() {
+        let legacy = read_legacy(crate::ID, legacy_data()).unwrap();
+        assert_eq!(legacy.day_uri, "test.com/day");
+        assert_eq!(legacy.night_uri, "test.com/night");
+        assert_eq!(legacy.state, 1);
+        assert_eq!(legacy.thread_id, b"nightfury");
+        assert_eq!(legacy.bump, 254);
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        let invalid = NightFuryError::InvalidLegacyConfig.into();
+        assert_eq!(
+            read_legacy(Pubkey::new_unique(), legacy_data()).unwrap_err(),
+            invalid
+        );
+
+        let mut resized = legacy_data();
+        resized.resize(NightFury::space(2), 0);
+        assert_eq!(read_legacy(crate::ID, resized).unwrap_err(), invalid);
+
+        let mut wrong_discriminator = legacy_data();
+        wrong_discriminator[..8].copy_from_slice(&Batch::discriminator());
+        assert_eq!(
+            read_legacy(crate::ID, wrong_discriminator).unwrap_err(),
+            invalid
+        );
+    }
+
+    fn phase(uri: &str) -> Phase {
+        Phase {
+            uri: uri.to_string(),
+            name: None,
+            symbol: None,
+        }
+    }
+
+    fn named(uri: &str, name: &str, symbol: &str) -> Phase {
+        Phase {
+            name: Some(name.to_string()),
+            symbol: Some(symbol.to_string()),
+            ..phase(uri)
+        }
+    }
+
+    #[test]
+    fn validates_the_phase_count() {
+        let phases = vec![phase("test.com/phase"); NightFury::MAX_PHASES + 1];
+        for count in [0, 1, NightFury::MAX_PHASES + 1] {
+            assert_eq!(
+                NightFury::validate_phases(&phases[..count]).unwrap_err(),
+                NightFuryError::InvalidPhaseCount.into()
+            );
+        }
+        for count in 2..=NightFury::MAX_PHASES {
+            assert!(NightFury::validate_phases(&phases[..count]).is_ok());
+        }
+    }
+
+    #[test]
+    fn names_and_symbols_are_set_by_all_phases_or_none() {
+        let day = named("test.com/day", "Day", "DAY");
+        let night = named("test.com/night", "Night", "NIGHT");
+        assert!(NightFury::validate_phases(&[day.clone(), night.clone()]).is_ok());
+        assert!(
+            NightFury::validate_phases(&[phase("test.com/day"), phase("test.com/night")]).is_ok()
+        );
+
+        let unnamed_night = Phase {
+            name: None,
+            ..night.clone()
+        };
+        let no_symbol_night = Phase {
+            symbol: None,
+            ..night
+        };
+        for phases in [[day.clone(), unnamed_night], [day, no_symbol_night]] {
+            assert_eq!(
+                NightFury::validate_phases(&phases).unwrap_err(),
+                NightFuryError::InconsistentPhases.into()
+            );
+        }
+    }
+
+    #[test]
+    fn validates_the_lengths_against_token_metadata() {
+        let longest = named(
+            &"u".repeat(MAX_URI_LENGTH),
+            &"n".repeat(MAX_NAME_LENGTH),
+            &"s".repeat(MAX_SYMBOL_LENGTH),
+        );
+        assert!(longest.validate().is_ok());
+
+        let cases = [
+            (
+                Phase {
+                    uri: "u".repeat(MAX_URI_LENGTH + 1),
+                    ..longest.clone()
+                },
+                NightFuryError::UriTooLong,
+            ),
+            (
+                Phase {
+                    name: Some("n".repeat(MAX_NAME_LENGTH + 1)),
+                    ..longest.clone()
+                },
+                NightFuryError::NameTooLong,
+            ),
+            (
+                Phase {
+                    symbol: Some("s".repeat(MAX_SYMBOL_LENGTH + 1)),
+                    ..longest.clone()
+                },
+                NightFuryError::SymbolTooLong,
+            ),
+        ];
+        for (phase, error) in cases {
+            assert_eq!(phase.validate().unwrap_err(), error.into());
+            assert_eq!(
+                NightFury::validate_phases(&[longest.clone(), phase]).unwrap_err(),
+                error.into()
+            );
+        }
+    }
+
+    #[test]
+    fn validates_the_expiry() {
+        let expiry = Expiry {
+            end_at: Some(2_000),
+            max_switches: None,
+            final_phase: 1,
+        };
+        assert!(expiry.validate(2, 1_999).is_ok());
+        assert!(Expiry {
+            end_at: None,
+            max_switches: Some(1),
+            ..expiry.clone()
+        }
+        .validate(2, 1_999)
+        .is_ok());
+
+        let cases = [
+            (
+                Expiry {
+                    final_phase: 2,
+                    ..expiry.clone()
+                },
+                NightFuryError::InvalidPhaseIndex,
+            ),
+            (
+                Expiry {
+                    end_at: None,
+                    ..expiry.clone()
+                },
+                NightFuryError::InvalidExpiry,
+            ),
+            (
+                Expiry {
+                    max_switches: Some(0),
+                    ..expiry.clone()
+                },
+                NightFuryError::InvalidExpiry,
+            ),
+        ];
+        for (expiry, error) in cases {
+            assert_eq!(expiry.validate(2, 1_999).unwrap_err(), error.into());
+        }
+        // An end that's already here or gone can't be set.
+        for now in [2_000, 2_001] {
+            assert_eq!(
+                expiry.validate(2, now).unwrap_err(),
+                NightFuryError::InvalidExpiry.into()
+            );
+        }
+    }
+
+    #[test]
+    fn expiry_is_reached_at_the_end_or_the_switch_limit() {
+        let expiry = Expiry {
+            end_at: Some(2_000),
+            max_switches: Some(3),
+            final_phase: 0,
+        };
+        assert!(!expiry.is_reached(1_999, 2));
+        assert!(expiry.is_reached(2_000, 0));
+        assert!(expiry.is_reached(1_000, 3));
+        assert!(expiry.is_reached(1_000, 4));
+
+        let by_count = Expiry {
+            end_at: None,
+            ..expiry.clone()
+        };
+        assert!(!by_count.is_reached(i64::MAX, 2));
+        assert!(by_count.is_reached(0, 3));
+
+        let by_time = Expiry {
+            max_switches: None,
+            ..expiry
+        };
+        assert!(!by_time.is_reached(1_999, u32::MAX));
+        assert!(by_time.is_reached(2_000, 0));
+    }
+
+    #[test]
+    fn validates_the_thread_config() {
+        let config = ThreadConfig {
+            fee: ThreadConfig::MIN_FEE,
+            rate_limit: 1,
+            name: "n".repeat(ThreadConfig::MAX_NAME_LENGTH),
+            skippable: false,
+        };
+        assert!(config.validate().is_ok());
+        assert!(ThreadConfig::default().validate().is_ok());
+        assert!(ThreadConfig {
+            fee: ThreadConfig::MAX_FEE,
+            ..config.clone()
+        }
+        .validate()
+        .is_ok());
+
+        let cases = [
+            (
+                ThreadConfig {
+                    fee: ThreadConfig::MIN_FEE - 1,
+                    ..config.clone()
+                },
+                NightFuryError::InvalidThreadFee,
+            ),
+            (
+                ThreadConfig {
+                    fee: ThreadConfig::MAX_FEE + 1,
+                    ..config.clone()
+                },
+                NightFuryError::InvalidThreadFee,
+            ),
+            (
+                ThreadConfig {
+                    rate_limit: 0,
+                    ..config.clone()
+                },
+                NightFuryError::InvalidRateLimit,
+            ),
+            (
+                ThreadConfig {
+                    name: "n".repeat(ThreadConfig::MAX_NAME_LENGTH + 1),
+                    ..config
+                },
+                NightFuryError::NameTooLong,
+            ),
+        ];
+        for (config, error) in cases {
+            assert_eq!(config.validate().unwrap_err(), error.into());
+        }
+    }
+
+    #[test]
+    fn validates_the_thread_id() {
+        let thread_id = [7; NightFury::MAX_THREAD_ID_LENGTH + 1];
+        assert_eq!(
+            NightFury::thread_id_seed(&thread_id[..NightFury::MAX_THREAD_ID_LENGTH]).unwrap(),
+            &thread_id[..NightFury::MAX_THREAD_ID_LENGTH]
+        );
+        assert_eq!(
+            NightFury::thread_id_seed(&thread_id).unwrap_err(),
+            NightFuryError::ThreadIdTooLong.into()
+        );
+    }
 }
@@ -1,4 +1,4 @@
-import { Transaction, VersionedTransaction, TransactionMessage, SystemProgram, PublicKey, Connection, Keypair, ComputeBudgetProgram, SYSVAR_INSTRUCTIONS_PUBKEY, clusterApiUrl } from "@solana/web3.js";
+import { Transaction, VersionedTransaction, TransactionMessage, SystemProgram, PublicKey, Connection, Keypair, ComputeBudgetProgram, SYSVAR_INSTRUCTIONS_PUBKEY, clusterApiUrl, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import * as anchor from "@project-serum/anchor";
 import { ClockworkProvider } from "@clockwork-xyz/sdk";
 import { Program } from "@project-serum/anchor";
@@ -74,7 +74,7 @@ const setupNightFury = async ([nft, adminKeypair], config: NightFuryConfig) => {
 
     let [delegateRecordAddress] = findDelegateRecordAddress(
         nft.mint.address,
-        threadAddress,
+        nightFuryAddress,
         adminKeypair.publicKey,
     );
 
@@ -92,6 +92,7 @@ const setupNightFury = async ([nft, adminKeypair], config: NightFuryConfig) => {
         authority: adminKeypair.publicKey,
         threadAuthority: threadAuthorityAddress,
         thread: threadAddress,
+        escrow: findEscrowAddress(nightFuryAddress, program.programId)[0],
         authorizationRules: nft.programmableConfig.ruleSet,
         instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
         threadProgram: clockworkProvider.threadProgram.programId,
@@ -104,7 +105,18 @@ const setupNightFury = async ([nft, adminKeypair], config: NightFuryConfig) => {
         console.log(accounts[account].toString());
     }
 
-    const initIx = await program.methods.initialize(config.threadId, config.dayURI, config.nightURI).accounts({
+    const initIx = await program.methods.initialize(config.threadId, {
+        phases: [{ uri: config.dayURI }, { uri: config.nightURI }],
+        // Day from 06:00 to 18:00, night for the other half.
+        mode: { cycle: { period: new anchor.BN(12 * 60 * 60), offset: new anchor.BN(6 * 60 * 60) } },
+        timezone: { utcOffset: 0, dst: { none: {} } },
+        expiry: null,
+        holderSwitch: null,
+        scheduler: { clockwork: {} },
+        schedule: "00 00 * * * * *",
+        funding: new anchor.BN(LAMPORTS_PER_SOL),
+        threadConfig: null,
+    }).accounts({
         nightfury: nightFuryAddress,
         mint: nft.mint.address,
         masterEdition: nft.edition.address,
@@ -112,6 +124,7 @@ const setupNightFury = async ([nft, adminKeypair], config: NightFuryConfig) => {
         metadata: nft.metadataAddress,
         authority: adminKeypair.publicKey,
         thread: threadAddress,
+        escrow: findEscrowAddress(nightFuryAddress, program.programId)[0],
         authorizationRules: nft.programmableConfig.ruleSet,
         instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
         threadProgram: clockworkProvider.threadProgram.programId,
@@ -178,7 +191,7 @@ const revokeNightFury = async ([nft, adminKeypair], config: NightFuryConfig) =>
 
     let [delegateRecordAddress] = findDelegateRecordAddress(
         nft.mint.address,
-        threadAddress,
+        nightFuryAddress,
         adminKeypair.publicKey,
     );
 
@@ -218,6 +231,8 @@ const revokeNightFury = async ([nft, adminKeypair], config: NightFuryConfig) =>
         nightfury: nightFuryAddress,
         // thread: threadAddress,
         thread: new PublicKey("6VmTrSU7tnHN591HuToZ1SA4R21pVgcsKu33Tx7YvHWQ"),
+        escrow: findEscrowAddress(nightFuryAddress, program.programId)[0],
+        overrides: findOverridesAddress(nightFuryAddress, program.programId)[0],
         mint: nft.mint.address,
         metadata: nft.metadataAddress,
         masterEdition: nft.edition.address,
@@ -280,6 +295,26 @@ let findNightFuryAddress = (
     ], programId);
 };
 
+let findEscrowAddress = (
+    nightfuryAddress: PublicKey,
+    programId: PublicKey,
+) => {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), nightfuryAddress.toBuffer()],
+        programId,
+    );
+};
+
+let findOverridesAddress = (
+    nightfuryAddress: PublicKey,
+    programId: PublicKey,
+) => {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("overrides"), nightfuryAddress.toBuffer()],
+        programId,
+    );
+};
+
 let findThreadAuthorityAddress = (
     nightfuryAddress: PublicKey,
     programId: PublicKey,
@@ -393,7 +428,7 @@ const updateNightfury = async ([nft, adminKeypair], config: NightFuryConfig) =>
 
     let [delegateRecordAddress] = findDelegateRecordAddress(
         nft.mint.address,
-        threadAddress,
+        nightFuryAddress,
         adminKeypair.publicKey,
     );
 
@@ -453,6 +488,7 @@ const updateNightfury = async ([nft, adminKeypair], config: NightFuryConfig) =>
         mint: nft.mint.address,
         nightfury: nightFuryAddress,
         thread: threadAddress,
+        escrow: findEscrowAddress(nightFuryAddress, program.programId)[0],
         threadProgram: clockworkProvider.threadProgram.programId,
         systemProgram: SystemProgram.programId
     }).instruction();
@@ -0,0 +1,96 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { ClockworkProvider } from "@clockwork-xyz/sdk";
+import { Nightfury } from "../target/types/nightfury";
+import { LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import {
+  createPnft,
+  findBatchAddress,
+  initializeConfig,
+  metadataUri,
+  metaplexFor,
+  RULESET,
+  sleep,
+} from "./utils";
+
+describe("batch", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Nightfury as Program<Nightfury>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const clockworkProvider = ClockworkProvider.fromAnchorProvider(provider);
+  const authority = (provider.wallet as anchor.Wallet).payer;
+
+  it("Switches every member, a page at a time", async () => {
+    // Two members a page, so three members take two pages.
+    const threadId = Buffer.from("batch");
+    const [batchAddress] = findBatchAddress(
+      authority.publicKey,
+      threadId,
+      program.programId,
+    );
+    const [threadAddress] = clockworkProvider.getThreadPDA(
+      batchAddress,
+      threadId.toString(),
+    );
+    await program.methods
+      .createBatch(
+        threadId,
+        "*/10 * * * * * *",
+        2,
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+      )
+      .accounts({
+        authority: authority.publicKey,
+        batch: batchAddress,
+        thread: threadAddress,
+        authorizationRules: RULESET,
+        threadProgram: clockworkProvider.threadProgram.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const metaplex = metaplexFor(provider.connection, authority);
+    const members: { nightfury: PublicKey; metadata: PublicKey }[] = [];
+    for (let i = 0; i < 3; i++) {
+      const pnft = await createPnft(metaplex, authority, "test.com/minted");
+      const nightfury = await initializeConfig(
+        program,
+        clockworkProvider,
+        authority,
+        pnft,
+        Buffer.from("member"),
+        { scheduler: { batch: { batch: batchAddress } } },
+      );
+      await program.methods
+        .addToBatch()
+        .accounts({
+          authority: authority.publicKey,
+          batch: batchAddress,
+          nightfury,
+          metadata: pnft.metadataAddress,
+        })
+        .rpc();
+      members.push({ nightfury, metadata: pnft.metadataAddress });
+    }
+    const batch = await program.account.batch.fetch(batchAddress);
+    assert.equal(batch.members.length, 3);
+
+    // Wait for a run to reach the last page, then every member shows its phase.
+    const switched = async () => {
+      for (const { nightfury, metadata } of members) {
+        const config = await program.account.nightFury.fetch(nightfury);
+        const uri = await metadataUri(provider.connection, metadata);
+        if (uri !== config.phases[config.phase].uri) {
+          return false;
+        }
+      }
+      return true;
+    };
+    for (let attempt = 0; attempt < 12 && !(await switched()); attempt++) {
+      await sleep(10_000);
+    }
+    assert.isTrue(await switched());
+  });
+});
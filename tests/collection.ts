@@ -0,0 +1,346 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { ClockworkProvider } from "@clockwork-xyz/sdk";
+import { Nightfury } from "../target/types/nightfury";
+import {
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import { PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID } from "@metaplex-foundation/mpl-token-metadata";
+import * as mplAuth from "@metaplex-foundation/mpl-token-auth-rules";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  computeBudget,
+  createCollectionNft,
+  createPnft,
+  findCollectionAddress,
+  findDelegateRecordAddress,
+  findMemberAddress,
+  metadataUri,
+  metaplexFor,
+  RULESET,
+  sleep,
+} from "./utils";
+
+describe("collection", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Nightfury as Program<Nightfury>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const clockworkProvider = ClockworkProvider.fromAnchorProvider(provider);
+  const authority = (provider.wallet as anchor.Wallet).payer;
+  const metaplex = metaplexFor(provider.connection, authority);
+
+  let collectionMint: PublicKey;
+  let collectionAddress: PublicKey;
+
+  before(async () => {
+    const collectionNft = await createCollectionNft(metaplex, authority);
+    collectionMint = collectionNft.mintAddress;
+    [collectionAddress] = findCollectionAddress(
+      collectionMint,
+      program.programId,
+    );
+
+    // Two members a page, run every ten seconds.
+    const threadId = Buffer.from("collection");
+    const [threadAddress] = clockworkProvider.getThreadPDA(
+      collectionAddress,
+      threadId.toString(),
+    );
+    await program.methods
+      .createCollection(threadId, {
+        phases: [
+          { uri: "test.com/day", name: null, symbol: null },
+          { uri: "test.com/night", name: null, symbol: null },
+        ],
+        mode: {
+          cycle: { period: new anchor.BN(3600), offset: new anchor.BN(0) },
+        },
+        timezone: { utcOffset: 0, dst: { none: {} } },
+        schedule: "*/10 * * * * * *",
+        pageSize: 2,
+        funding: new anchor.BN(LAMPORTS_PER_SOL / 10),
+      })
+      .accounts({
+        authority: authority.publicKey,
+        collectionMint,
+        collectionMetadata: collectionNft.metadataAddress,
+        collection: collectionAddress,
+        thread: threadAddress,
+        authorizationRules: RULESET,
+        threadProgram: clockworkProvider.threadProgram.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  // The accounts a member's mint brings along, whichever way it's registered.
+  const memberAccounts = (pnft: {
+    mintAddress: PublicKey;
+    tokenAddress: PublicKey;
+    metadataAddress: PublicKey;
+    masterEditionAddress: PublicKey;
+  }) => ({
+    mint: pnft.mintAddress,
+    tokenAccount: pnft.tokenAddress,
+    metadata: pnft.metadataAddress,
+    masterEdition: pnft.masterEditionAddress,
+    delegateRecord: findDelegateRecordAddress(
+      pnft.mintAddress,
+      collectionAddress,
+      authority.publicKey,
+    )[0],
+  });
+
+  const nextMemberAddress = async () => {
+    const { nextIndex } = await program.account.collection.fetch(
+      collectionAddress,
+    );
+    const [member] = findMemberAddress(
+      collectionAddress,
+      nextIndex,
+      program.programId,
+    );
+    return member;
+  };
+
+  // Registers each mint under the member account it's paired with.
+  const batchRegister = (
+    entries: {
+      member: PublicKey;
+      pnft: Parameters<typeof memberAccounts>[0];
+    }[],
+  ) =>
+    program.methods
+      .batchRegister()
+      .accounts({
+        authority: authority.publicKey,
+        collection: collectionAddress,
+        authorizationRules: RULESET,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        authorizationRulesProgram: mplAuth.PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        entries.flatMap(({ member, pnft }) => {
+          const accounts = memberAccounts(pnft);
+          return [
+            { pubkey: member, isSigner: false, isWritable: true },
+            { pubkey: accounts.mint, isSigner: false, isWritable: false },
+            { pubkey: accounts.metadata, isSigner: false, isWritable: true },
+            {
+              pubkey: accounts.masterEdition,
+              isSigner: false,
+              isWritable: true,
+            },
+            {
+              pubkey: accounts.tokenAccount,
+              isSigner: false,
+              isWritable: false,
+            },
+            {
+              pubkey: accounts.delegateRecord,
+              isSigner: false,
+              isWritable: true,
+            },
+          ];
+        }),
+      )
+      .preInstructions([computeBudget()])
+      .rpc();
+
+  const assertFails = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, code);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  it("Batch registers collection mints under their member PDAs only", async () => {
+    const pnft = await createPnft(
+      metaplex,
+      authority,
+      "test.com/minted",
+      collectionMint,
+    );
+    const member = await nextMemberAddress();
+    const { nextIndex } = await program.account.collection.fetch(
+      collectionAddress,
+    );
+
+    // The member account has to be the PDA for the next index.
+    const [skipped] = findMemberAddress(
+      collectionAddress,
+      nextIndex + 1,
+      program.programId,
+    );
+    await assertFails(
+      batchRegister([{ member: skipped, pnft }]),
+      "InvalidMemberAccounts",
+    );
+
+    // Mints outside the collection are turned away.
+    const outsider = await createPnft(metaplex, authority, "test.com/minted");
+    await assertFails(
+      batchRegister([{ member, pnft: outsider }]),
+      "InvalidCollection",
+    );
+
+    await batchRegister([{ member, pnft }]);
+    const registered = await program.account.collectionMember.fetch(member);
+    assert.ok(registered.mint.equals(pnft.mintAddress));
+    assert.equal(registered.index, nextIndex);
+    const collection = await program.account.collection.fetch(
+      collectionAddress,
+    );
+    assert.equal(collection.nextIndex, nextIndex + 1);
+  });
+
+  it("Batch registers into a member PDA someone already sent lamports to", async () => {
+    const pnft = await createPnft(
+      metaplex,
+      authority,
+      "test.com/minted",
+      collectionMint,
+    );
+    const member = await nextMemberAddress();
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: authority.publicKey,
+          toPubkey: member,
+          lamports: 1,
+        }),
+      ),
+    );
+
+    await batchRegister([{ member, pnft }]);
+    const registered = await program.account.collectionMember.fetch(member);
+    assert.ok(registered.mint.equals(pnft.mintAddress));
+    const info = await provider.connection.getAccountInfo(member);
+    assert.ok(info.owner.equals(program.programId));
+    assert.isAtLeast(
+      info.lamports,
+      await provider.connection.getMinimumBalanceForRentExemption(
+        info.data.length,
+      ),
+    );
+  });
+
+  it("Switches every member, a page at a time, skipping removed ones", async () => {
+    const before = await program.account.collection.fetch(collectionAddress);
+    const pnfts = [];
+    const members = [];
+    for (let i = 0; i < 3; i++) {
+      const pnft = await createPnft(
+        metaplex,
+        authority,
+        "test.com/minted",
+        collectionMint,
+      );
+      const member = await nextMemberAddress();
+      await program.methods
+        .registerMember()
+        .accounts({
+          authority: authority.publicKey,
+          collection: collectionAddress,
+          member,
+          ...memberAccounts(pnft),
+          authorizationRules: RULESET,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          authorizationRulesProgram: mplAuth.PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([computeBudget()])
+        .rpc();
+      pnfts.push(pnft);
+      members.push(member);
+    }
+
+    // Leave a gap on the first page.
+    const { tokenAccount, ...removed } = memberAccounts(pnfts[1]);
+    await program.methods
+      .removeMember()
+      .accounts({
+        authority: authority.publicKey,
+        collection: collectionAddress,
+        member: members[1],
+        ...removed,
+        authorizationRules: RULESET,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        authorizationRulesProgram: mplAuth.PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([computeBudget()])
+      .rpc();
+    const collection = await program.account.collection.fetch(
+      collectionAddress,
+    );
+    assert.equal(collection.memberCount, before.memberCount + 2);
+    assert.equal(collection.nextIndex, before.nextIndex + 3);
+
+    // Wait for a run to reach the second page.
+    const switched = async () => {
+      const { phases, phase } = await program.account.collection.fetch(
+        collectionAddress,
+      );
+      for (const pnft of [pnfts[0], pnfts[2]]) {
+        const uri = await metadataUri(provider.connection, pnft.metadataAddress);
+        if (uri !== phases[phase].uri) {
+          return false;
+        }
+      }
+      return true;
+    };
+    for (let attempt = 0; attempt < 12 && !(await switched()); attempt++) {
+      await sleep(10_000);
+    }
+    assert.isTrue(await switched());
+    assert.equal(
+      await metadataUri(provider.connection, pnfts[1].metadataAddress),
+      "test.com/minted",
+    );
+  });
+
+  it("Updates the shared schedule and pauses and resumes the collection", async () => {
+    const { thread } = await program.account.collection.fetch(
+      collectionAddress,
+    );
+    const accounts = {
+      authority: authority.publicKey,
+      collection: collectionAddress,
+      thread,
+      threadProgram: clockworkProvider.threadProgram.programId,
+    };
+
+    await program.methods
+      .updateCollectionSchedule("*/30 * * * * * *")
+      .accounts({ ...accounts, systemProgram: SystemProgram.programId })
+      .rpc();
+    const collection = await program.account.collection.fetch(
+      collectionAddress,
+    );
+    assert.equal(collection.schedule, "*/30 * * * * * *");
+    const { trigger } = await clockworkProvider.getThreadAccount(thread);
+    assert.equal(trigger.cron.schedule, "*/30 * * * * * *");
+
+    await program.methods.pauseCollection().accounts(accounts).rpc();
+    assert.isTrue((await clockworkProvider.getThreadAccount(thread)).paused);
+    await program.methods.resumeCollection().accounts(accounts).rpc();
+    assert.isFalse((await clockworkProvider.getThreadAccount(thread)).paused);
+  });
+});
@@ -0,0 +1,211 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { ClockworkProvider } from "@clockwork-xyz/sdk";
+import { Nightfury } from "../target/types/nightfury";
+import {
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import { PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID } from "@metaplex-foundation/mpl-token-metadata";
+import * as mplAuth from "@metaplex-foundation/mpl-token-auth-rules";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  computeBudget,
+  createPnft,
+  findDelegateRecordAddress,
+  findEscrowAddress,
+  findNightFuryAddress,
+  findOverridesAddress,
+  initializeConfig,
+  metaplexFor,
+  RULESET,
+} from "./utils";
+
+describe("migrate_thread", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.Nightfury as Program<Nightfury>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const clockworkProvider = ClockworkProvider.fromAnchorProvider(provider);
+  const authority = (provider.wallet as anchor.Wallet).payer;
+
+  it("Carries state, delegate and overrides over to the new thread", async () => {
+    const pnft = await createPnft(
+      metaplexFor(provider.connection, authority),
+      authority,
+      "test.com/minted",
+    );
+    const threadId = Buffer.from("before");
+    const nightfuryAddress = await initializeConfig(
+      program,
+      clockworkProvider,
+      authority,
+      pnft,
+      threadId,
+      {
+        scheduler: { crank: { bounty: new anchor.BN(1_000) } },
+        funding: new anchor.BN(LAMPORTS_PER_SOL / 10),
+      },
+    );
+    const [threadAddress] = clockworkProvider.getThreadPDA(
+      nightfuryAddress,
+      threadId.toString(),
+    );
+    const [overridesAddress] = findOverridesAddress(
+      nightfuryAddress,
+      program.programId,
+    );
+    const [escrowAddress] = findEscrowAddress(
+      nightfuryAddress,
+      program.programId,
+    );
+    const [delegateRecordAddress] = findDelegateRecordAddress(
+      pnft.mintAddress,
+      nightfuryAddress,
+      authority.publicKey,
+    );
+
+    // An override and a pause, both of which have to survive the move.
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .addOverride(new anchor.BN(now), new anchor.BN(now + 3600), {
+        uri: "test.com/override",
+        name: null,
+        symbol: null,
+      })
+      .accounts({
+        authority: authority.publicKey,
+        nightfury: nightfuryAddress,
+        overrides: overridesAddress,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .pause()
+      .accounts({
+        authority: authority.publicKey,
+        mint: pnft.mintAddress,
+        nightfury: nightfuryAddress,
+        thread: threadAddress,
+        escrow: escrowAddress,
+        threadProgram: clockworkProvider.threadProgram.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const before = await program.account.nightFury.fetch(nightfuryAddress);
+    const overridesBefore = await program.account.overrides.fetch(
+      overridesAddress,
+    );
+
+    const newThreadId = Buffer.from("after");
+    const [newNightfuryAddress] = findNightFuryAddress(
+      pnft.mintAddress,
+      authority.publicKey,
+      newThreadId,
+      program.programId,
+    );
+    const [newThreadAddress] = clockworkProvider.getThreadPDA(
+      newNightfuryAddress,
+      newThreadId.toString(),
+    );
+    const [newOverridesAddress] = findOverridesAddress(
+      newNightfuryAddress,
+      program.programId,
+    );
+    const [newEscrowAddress] = findEscrowAddress(
+      newNightfuryAddress,
+      program.programId,
+    );
+    const [newDelegateRecordAddress] = findDelegateRecordAddress(
+      pnft.mintAddress,
+      newNightfuryAddress,
+      authority.publicKey,
+    );
+    // Anyone can send lamports to the new overrides PDA first, which mustn't block the move.
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: authority.publicKey,
+          toPubkey: newOverridesAddress,
+          lamports: 1,
+        }),
+      ),
+    );
+
+    const funding = LAMPORTS_PER_SOL / 20;
+    await program.methods
+      .migrateThread(newThreadId, new anchor.BN(funding))
+      .accounts({
+        authority: authority.publicKey,
+        nightfury: nightfuryAddress,
+        newNightfury: newNightfuryAddress,
+        overrides: overridesAddress,
+        newOverrides: newOverridesAddress,
+        thread: threadAddress,
+        newThread: newThreadAddress,
+        escrow: escrowAddress,
+        newEscrow: newEscrowAddress,
+        mint: pnft.mintAddress,
+        tokenAccount: pnft.tokenAddress,
+        metadata: pnft.metadataAddress,
+        masterEdition: pnft.masterEditionAddress,
+        delegateRecord: delegateRecordAddress,
+        newDelegateRecord: newDelegateRecordAddress,
+        authorizationRules: RULESET,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        threadProgram: clockworkProvider.threadProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        authorizationRulesProgram: mplAuth.PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([computeBudget()])
+      .rpc();
+
+    // The old config is gone, along with its overrides and delegate.
+    for (const address of [
+      nightfuryAddress,
+      overridesAddress,
+      delegateRecordAddress,
+    ]) {
+      assert.isNull(await provider.connection.getAccountInfo(address));
+    }
+    assert.equal(await provider.connection.getBalance(escrowAddress), 0);
+
+    // Everything but the thread carries over, the pause included.
+    const after = await program.account.nightFury.fetch(newNightfuryAddress);
+    const { thread, threadId: _, bump, ...state } = before;
+    const {
+      thread: newThread,
+      threadId: newThreadIdAfter,
+      bump: newBump,
+      ...newState
+    } = after;
+    assert.equal(JSON.stringify(newState), JSON.stringify(state));
+    assert.deepEqual(newState.status, { paused: {} });
+    assert.ok(newThread.equals(newThreadAddress));
+    assert.deepEqual(Buffer.from(newThreadIdAfter), newThreadId);
+
+    const overridesAfter = await program.account.overrides.fetch(
+      newOverridesAddress,
+    );
+    assert.ok(overridesAfter.nightfury.equals(newNightfuryAddress));
+    assert.equal(
+      JSON.stringify(overridesAfter.entries),
+      JSON.stringify(overridesBefore.entries),
+    );
+
+    // The new config holds the delegate and starts out with the new funding.
+    const delegateRecord = await provider.connection.getAccountInfo(
+      newDelegateRecordAddress,
+    );
+    assert.ok(delegateRecord.owner.equals(TOKEN_METADATA_PROGRAM_ID));
+    assert.equal(
+      await provider.connection.getBalance(newEscrowAddress),
+      funding,
+    );
+  });
+});
@@ -0,0 +1,250 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { MockOracle } from "../target/types/mock_oracle";
+import { Nightfury } from "../target/types/nightfury";
+import { ClockworkProvider } from "@clockwork-xyz/sdk";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  Metadata,
+  PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID,
+  TokenStandard,
+} from "@metaplex-foundation/mpl-token-metadata";
+import {
+  keypairIdentity,
+  Metaplex,
+  mockStorage,
+} from "@metaplex-foundation/js";
+import * as mplAuth from "@metaplex-foundation/mpl-token-auth-rules";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+
+describe("mock-oracle", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.MockOracle as Program<MockOracle>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+
+  it("Writes a Pyth-style price account", async () => {
+    const price = await createOracleAccount(provider, program.programId, 240);
+
+    // SOL at $21.50 with an exponent of -8, trading.
+    await program.methods
+      .setPrice(new anchor.BN(2_150_000_000), new anchor.BN(1_000_000), -8, 1)
+      .accounts({ price: price.publicKey })
+      .rpc();
+
+    const { data } = await provider.connection.getAccountInfo(price.publicKey);
+    assert.equal(data.readUInt32LE(0), 0xa1b2c3d4);
+    assert.equal(data.readUInt32LE(8), 3);
+    assert.equal(data.readInt32LE(20), -8);
+    assert.equal(data.readBigInt64LE(208), BigInt(2_150_000_000));
+    assert.equal(data.readUInt32LE(224), 1);
+  });
+
+  it("Writes a generic data feed", async () => {
+    const feed = await createOracleAccount(provider, program.programId, 20);
+
+    // Weather code 2 ("rainy"), published an hour ago.
+    const timestamp = Math.floor(Date.now() / 1000) - 3600;
+    await program.methods
+      .setValue(new anchor.BN(2), new anchor.BN(timestamp))
+      .accounts({ feed: feed.publicKey })
+      .rpc();
+
+    const { data } = await provider.connection.getAccountInfo(feed.publicKey);
+    assert.equal(data.readUInt32LE(0), 0x4e464446);
+    assert.equal(data.readBigUInt64LE(4), BigInt(2));
+    assert.equal(data.readBigInt64LE(12), BigInt(timestamp));
+  });
+
+  it("Switches a PriceFeed config on the price", async () => {
+    const nightfury = anchor.workspace.Nightfury as Program<Nightfury>;
+    const clockworkProvider = ClockworkProvider.fromAnchorProvider(provider);
+    const authority = (provider.wallet as anchor.Wallet).payer;
+    const rulesetAddress = new PublicKey(
+      "eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9",
+    );
+
+    const price = await createOracleAccount(provider, program.programId, 240);
+    const setPrice = (value: number) =>
+      program.methods
+        .setPrice(new anchor.BN(value), new anchor.BN(1_000_000), -8, 1)
+        .accounts({ price: price.publicKey })
+        .rpc();
+    await setPrice(1_500_000_000);
+
+    const metaplex = new Metaplex(provider.connection)
+      .use(keypairIdentity(authority))
+      .use(mockStorage());
+    const pnft = await metaplex.nfts().create({
+      tokenStandard: TokenStandard.ProgrammableNonFungible,
+      sellerFeeBasisPoints: 500,
+      ruleSet: rulesetAddress,
+      uri: "test.com/minted",
+      name: "Bull Or Bear",
+      symbol: "SOL",
+      creators: [{ address: authority.publicKey, share: 100 }],
+    });
+
+    // Cranked by the test itself, every second, for no bounty.
+    const threadId = Buffer.from("price");
+    const [nightfuryAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("nightfury"),
+        pnft.mintAddress.toBuffer(),
+        authority.publicKey.toBuffer(),
+        threadId,
+      ],
+      nightfury.programId,
+    );
+    const [threadAddress] = clockworkProvider.getThreadPDA(
+      nightfuryAddress,
+      threadId.toString(),
+    );
+    const [delegateRecordAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        pnft.mintAddress.toBuffer(),
+        Buffer.from("data_item_delegate"),
+        authority.publicKey.toBuffer(),
+        nightfuryAddress.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID,
+    );
+    const [escrowAddress] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), nightfuryAddress.toBuffer()],
+      nightfury.programId,
+    );
+    const [overridesAddress] = PublicKey.findProgramAddressSync(
+      [Buffer.from("overrides"), nightfuryAddress.toBuffer()],
+      nightfury.programId,
+    );
+    const computeBudget = ComputeBudgetProgram.setComputeUnitLimit({
+      units: 400_000,
+    });
+
+    // $20 and up is bullish.
+    await nightfury.methods
+      .initialize(threadId, {
+        phases: [
+          { uri: "test.com/bear", name: null, symbol: null },
+          { uri: "test.com/bull", name: null, symbol: null },
+        ],
+        mode: {
+          priceFeed: {
+            feed: price.publicKey,
+            maxAge: 60,
+            thresholds: [new anchor.BN(2_000_000_000)],
+          },
+        },
+        timezone: { utcOffset: 0, dst: { none: {} } },
+        expiry: null,
+        holderSwitch: null,
+        scheduler: { crank: { bounty: new anchor.BN(0) } },
+        schedule: "* * * * * * *",
+        funding: new anchor.BN(0),
+        threadConfig: null,
+      })
+      .accounts({
+        nightfury: nightfuryAddress,
+        mint: pnft.mintAddress,
+        tokenAccount: pnft.tokenAddress,
+        metadata: pnft.metadataAddress,
+        masterEdition: pnft.masterEditionAddress,
+        authority: authority.publicKey,
+        delegateRecord: delegateRecordAddress,
+        thread: threadAddress,
+        escrow: escrowAddress,
+        authorizationRules: rulesetAddress,
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        threadProgram: clockworkProvider.threadProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        authorizationRulesProgram: mplAuth.PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([computeBudget])
+      .rpc();
+
+    // Crank once the schedule is due, then read back the phase and the metadata uri.
+    const crank = async () => {
+      await sleep(1500);
+      await nightfury.methods
+        .switch()
+        .accounts({
+          nightfury: nightfuryAddress,
+          overrides: overridesAddress,
+          mint: pnft.mintAddress,
+          metadata: pnft.metadataAddress,
+          masterEdition: pnft.masterEditionAddress,
+          delegateRecord: delegateRecordAddress,
+          signer: authority.publicKey,
+          escrow: escrowAddress,
+          oracle: price.publicKey,
+          authRules: rulesetAddress,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authorizationRulesProgram: mplAuth.PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([computeBudget])
+        .rpc();
+      const { phase } = await nightfury.account.nightFury.fetch(
+        nightfuryAddress,
+      );
+      const metadata = await Metadata.fromAccountAddress(
+        provider.connection,
+        pnft.metadataAddress,
+      );
+      return [phase, metadata.data.uri.replace(/\0/g, "")];
+    };
+
+    // $15.
+    assert.deepEqual(await crank(), [0, "test.com/bear"]);
+    // $25.
+    await setPrice(2_500_000_000);
+    assert.deepEqual(await crank(), [1, "test.com/bull"]);
+    // Exactly $20 is still bullish.
+    await setPrice(2_000_000_000);
+    assert.deepEqual(await crank(), [1, "test.com/bull"]);
+    // $19.99.
+    await setPrice(1_999_000_000);
+    assert.deepEqual(await crank(), [0, "test.com/bear"]);
+  });
+});
+
+const sleep = (ms: number) => new Promise((r) => setTimeout(r, ms));
+
+// Oracle accounts are plain program-owned accounts the mock writes raw bytes into.
+export const createOracleAccount = async (
+  provider: anchor.AnchorProvider,
+  programId: anchor.web3.PublicKey,
+  space: number,
+) => {
+  const account = Keypair.generate();
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(
+    space,
+  );
+  await provider.sendAndConfirm(
+    new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: provider.wallet.publicKey,
+        newAccountPubkey: account.publicKey,
+        lamports,
+        space,
+        programId,
+      }),
+    ),
+    [account],
+  );
+  return account;
+};
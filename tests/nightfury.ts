@@ -93,9 +93,13 @@ describe("nightfury", () => {
     );
     let [delegateRecordAddress] = findDelegateRecordAddress(
       pnft.mintAddress,
-      threadAddress,
+      nightfuryAddress,
       authorityKeypair.publicKey,
     );
+    const [escrowAddress] = findEscrowAddress(
+      nightfuryAddress,
+      program.programId,
+    );
     const accounts = {
       nightfury: nightfuryAddress,
       mint: pnft.mintAddress,
@@ -105,6 +109,7 @@ describe("nightfury", () => {
       metadata: pnft.metadataAddress,
       authority: authorityKeypair.publicKey,
       thread: threadAddress,
+      escrow: escrowAddress,
       authorizationRules: rulesetAddress,
       instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
       threadProgram: clockworkProvider.threadProgram.programId,
@@ -120,8 +125,17 @@ describe("nightfury", () => {
     });
     const initializeIx = await program.methods.initialize(
       threadId,
-      "test.com/day",
-      "test.com/night",
+      {
+        phases: [{ uri: "test.com/day" }, { uri: "test.com/night" }],
+        mode: { cycle: { period: new anchor.BN(30), offset: new anchor.BN(0) } },
+        timezone: { utcOffset: 0, dst: { none: {} } },
+        expiry: null,
+        holderSwitch: null,
+        scheduler: { clockwork: {} },
+        schedule: "*/30 * * * * * *",
+        funding: new anchor.BN(LAMPORTS_PER_SOL),
+        threadConfig: null,
+      },
     ).accounts({
       nightfury: nightfuryAddress,
       mint: pnft.mintAddress,
@@ -131,6 +145,7 @@ describe("nightfury", () => {
       metadata: pnft.metadataAddress,
       authority: authorityKeypair.publicKey,
       thread: threadAddress,
+      escrow: escrowAddress,
       authorizationRules: rulesetAddress,
       instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
       threadProgram: clockworkProvider.threadProgram.programId,
@@ -161,6 +176,8 @@ describe("nightfury", () => {
       metadata: pnft.metadataAddress,
       authority: authorityKeypair.publicKey,
       thread: threadAddress,
+      escrow: escrowAddress,
+      overrides: findOverridesAddress(nightfuryAddress, program.programId)[0],
       authorizationRules: rulesetAddress,
       instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
       threadProgram: clockworkProvider.threadProgram.programId,
@@ -221,6 +238,26 @@ let findNightFuryAddress = (
   ], programId);
 };
 
+let findEscrowAddress = (
+  nightfuryAddress: PublicKey,
+  programId: PublicKey,
+) => {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("escrow"), nightfuryAddress.toBuffer()],
+    programId,
+  );
+};
+
+let findOverridesAddress = (
+  nightfuryAddress: PublicKey,
+  programId: PublicKey,
+) => {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("overrides"), nightfuryAddress.toBuffer()],
+    programId,
+  );
+};
+
 let findThreadAuthorityAddress = (
   nightfuryAddress: PublicKey,
   programId: PublicKey,
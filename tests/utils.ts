@@ -0,0 +1,233 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { ClockworkProvider } from "@clockwork-xyz/sdk";
+import { Nightfury } from "../target/types/nightfury";
+import {
+  ComputeBudgetProgram,
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+import {
+  Metadata,
+  PROGRAM_ID as TOKEN_METADATA_PROGRAM_ID,
+  TokenStandard,
+} from "@metaplex-foundation/mpl-token-metadata";
+import {
+  keypairIdentity,
+  Metaplex,
+  mockStorage,
+} from "@metaplex-foundation/js";
+import * as mplAuth from "@metaplex-foundation/mpl-token-auth-rules";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+// Shared by the tests that set configs, batches and collections up from scratch.
+
+// Rule set cloned from mainnet, see Anchor.toml.
+export const RULESET = new PublicKey(
+  "eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9",
+);
+
+export const computeBudget = () =>
+  ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 });
+
+export const sleep = (ms: number) => new Promise((r) => setTimeout(r, ms));
+
+export const metaplexFor = (connection: Connection, authority: Keypair) =>
+  new Metaplex(connection).use(keypairIdentity(authority)).use(mockStorage());
+
+// A collection parent NFT, updated by `authority`.
+export const createCollectionNft = async (
+  metaplex: Metaplex,
+  authority: Keypair,
+) =>
+  metaplex.nfts().create({
+    sellerFeeBasisPoints: 500,
+    uri: "test.com/collection",
+    name: "NightFury Collection",
+    symbol: "NIGHT",
+    isCollection: true,
+    creators: [{ address: authority.publicKey, share: 100 }],
+  });
+
+// A pNFT on the test rule set, a verified member of `collection` when given.
+export const createPnft = async (
+  metaplex: Metaplex,
+  authority: Keypair,
+  uri: string,
+  collection?: PublicKey,
+) =>
+  metaplex.nfts().create({
+    tokenStandard: TokenStandard.ProgrammableNonFungible,
+    sellerFeeBasisPoints: 500,
+    ruleSet: RULESET,
+    uri,
+    name: "Angry Evening",
+    symbol: "NIGHT",
+    collection,
+    collectionAuthority: collection ? authority : undefined,
+    creators: [{ address: authority.publicKey, share: 100 }],
+  });
+
+export const metadataUri = async (
+  connection: Connection,
+  metadata: PublicKey,
+) => {
+  const { data } = await Metadata.fromAccountAddress(connection, metadata);
+  return data.uri.replace(/\0/g, "");
+};
+
+export const findNightFuryAddress = (
+  mint: PublicKey,
+  authority: PublicKey,
+  threadId: Buffer,
+  programId: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("nightfury"),
+      mint.toBuffer(),
+      authority.toBuffer(),
+      threadId,
+    ],
+    programId,
+  );
+
+export const findEscrowAddress = (
+  nightfuryAddress: PublicKey,
+  programId: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("escrow"), nightfuryAddress.toBuffer()],
+    programId,
+  );
+
+export const findOverridesAddress = (
+  nightfuryAddress: PublicKey,
+  programId: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("overrides"), nightfuryAddress.toBuffer()],
+    programId,
+  );
+
+export const findBatchAddress = (
+  authority: PublicKey,
+  threadId: Buffer,
+  programId: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("batch"), authority.toBuffer(), threadId],
+    programId,
+  );
+
+export const findCollectionAddress = (
+  collectionMint: PublicKey,
+  programId: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("collection"), collectionMint.toBuffer()],
+    programId,
+  );
+
+export const findMemberAddress = (
+  collectionAddress: PublicKey,
+  index: number,
+  programId: PublicKey,
+) => {
+  const indexBytes = Buffer.alloc(4);
+  indexBytes.writeUInt32LE(index);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("member"), collectionAddress.toBuffer(), indexBytes],
+    programId,
+  );
+};
+
+export const findDelegateRecordAddress = (
+  mint: PublicKey,
+  delegate: PublicKey,
+  updateAuthority: PublicKey,
+) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+      Buffer.from("data_item_delegate"),
+      updateAuthority.toBuffer(),
+      delegate.toBuffer(),
+    ],
+    TOKEN_METADATA_PROGRAM_ID,
+  );
+
+// Initializes a config for `pnft` under `threadId`, returning its address. `args` overrides
+// the defaults: two phases cycling hourly, cranked by anyone for no bounty.
+export const initializeConfig = async (
+  program: Program<Nightfury>,
+  clockworkProvider: ClockworkProvider,
+  authority: Keypair,
+  pnft: {
+    mintAddress: PublicKey;
+    tokenAddress: PublicKey;
+    metadataAddress: PublicKey;
+    masterEditionAddress: PublicKey;
+  },
+  threadId: Buffer,
+  args: object = {},
+) => {
+  const [nightfuryAddress] = findNightFuryAddress(
+    pnft.mintAddress,
+    authority.publicKey,
+    threadId,
+    program.programId,
+  );
+  const [threadAddress] = clockworkProvider.getThreadPDA(
+    nightfuryAddress,
+    threadId.toString(),
+  );
+  await program.methods
+    .initialize(threadId, {
+      phases: [
+        { uri: "test.com/day", name: null, symbol: null },
+        { uri: "test.com/night", name: null, symbol: null },
+      ],
+      mode: {
+        cycle: { period: new anchor.BN(3600), offset: new anchor.BN(0) },
+      },
+      timezone: { utcOffset: 0, dst: { none: {} } },
+      expiry: null,
+      holderSwitch: null,
+      scheduler: { crank: { bounty: new anchor.BN(0) } },
+      schedule: "0 0 * * * * *",
+      funding: new anchor.BN(0),
+      threadConfig: null,
+      ...args,
+    })
+    .accounts({
+      nightfury: nightfuryAddress,
+      mint: pnft.mintAddress,
+      tokenAccount: pnft.tokenAddress,
+      metadata: pnft.metadataAddress,
+      masterEdition: pnft.masterEditionAddress,
+      authority: authority.publicKey,
+      delegateRecord: findDelegateRecordAddress(
+        pnft.mintAddress,
+        nightfuryAddress,
+        authority.publicKey,
+      )[0],
+      thread: threadAddress,
+      escrow: findEscrowAddress(nightfuryAddress, program.programId)[0],
+      authorizationRules: RULESET,
+      instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      threadProgram: clockworkProvider.threadProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      authorizationRulesProgram: mplAuth.PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .preInstructions([computeBudget()])
+    .rpc();
+  return nightfuryAddress;
+};